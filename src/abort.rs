@@ -0,0 +1,283 @@
+//! Aborting a run whose error rate crosses a threshold (--abort-on-error-rate).
+//!
+//! Workers report the outcome of every completed request. A watcher counts the outcomes of the last
+//! `--abort-window` and cancels the run as soon as the share of non-success outcomes among them goes
+//! above the threshold. The window is kept as a fixed number of slots, so its memory does not grow
+//! with the request rate.
+
+use std::{collections::VecDeque, fmt, time::Duration};
+
+use rust_decimal::prelude::ToPrimitive as _;
+use tokio::{select, sync::mpsc::Receiver, time::Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::work_unit::HttpRequestOutcome;
+
+/// Outcomes needed in the window before the error rate is judged, until a whole window has passed.
+/// Keeps the first few requests of a run from aborting it on their own.
+const MIN_REQUESTS: usize = 10;
+
+/// Slots the window is split into; outcomes leave the window one slot (1% of it) at a time.
+const SLOTS: u64 = 100;
+
+/// Abort settings (--abort-on-error-rate, --abort-window).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AbortPolicy {
+    /// Share of non-success outcomes, as a fraction, above which the run is aborted.
+    pub error_rate: f64,
+    /// Span of the rolling window the error rate is measured over.
+    pub window: Duration,
+}
+
+/// Why a run was aborted: the error rate measured when it crossed the threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AbortReason {
+    pub error_rate: f64,
+    pub threshold: f64,
+    pub window: Duration,
+    /// Outcomes in the window the error rate was measured over.
+    pub requests: usize,
+    /// Time from the start of the run to the abort.
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for AbortReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error rate {:.1}% over the last {} seconds ({} requests) exceeded {}%",
+            self.error_rate * 100.0_f64,
+            self.window.as_secs_f64(),
+            self.requests,
+            self.threshold * 100.0_f64
+        )
+    }
+}
+
+/// Outcomes completed in one slot of the window.
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    index: u64,
+    requests: usize,
+    failed: usize,
+}
+
+/// Outcomes completed within the last window, counted per slot, oldest slot first.
+#[derive(Debug)]
+pub(crate) struct ErrorWindow {
+    policy: AbortPolicy,
+    start: Instant,
+    slot_width: Duration,
+    slots: VecDeque<Slot>,
+    requests: usize,
+    failed: usize,
+}
+
+impl ErrorWindow {
+    pub(crate) fn new(policy: AbortPolicy, start: Instant) -> Self {
+        let slot_width = policy
+            .window
+            .checked_div(u32::try_from(SLOTS).expect("slot count fits in u32"))
+            .unwrap_or_default()
+            .max(Duration::from_nanos(1));
+        Self {
+            policy,
+            start,
+            slot_width,
+            slots: VecDeque::new(),
+            requests: 0,
+            failed: 0,
+        }
+    }
+
+    /// Index of the slot `at` falls in, counted from the start of the run.
+    fn slot_index(&self, at: Instant) -> u64 {
+        let elapsed = at.saturating_duration_since(self.start).as_nanos();
+        let index = elapsed
+            .checked_div(self.slot_width.as_nanos())
+            .unwrap_or_default();
+        u64::try_from(index).unwrap_or(u64::MAX)
+    }
+
+    /// Adds an outcome completed at `at` and returns the abort reason if the error rate over the
+    /// window ending there is now above the threshold.
+    pub(crate) fn record(
+        &mut self,
+        at: Instant,
+        outcome: HttpRequestOutcome,
+    ) -> Option<AbortReason> {
+        let failed = usize::from(outcome != HttpRequestOutcome::SuccessResponse);
+        let index = self.slot_index(at);
+        match self.slots.back_mut() {
+            Some(slot) if slot.index >= index => {
+                slot.requests = slot.requests.saturating_add(1);
+                slot.failed = slot.failed.saturating_add(failed);
+            }
+            Some(_) | None => self.slots.push_back(Slot {
+                index,
+                requests: 1,
+                failed,
+            }),
+        }
+        self.requests = self
+            .requests
+            .checked_add(1)
+            .expect("request count overflow");
+        self.failed = self
+            .failed
+            .checked_add(failed)
+            .expect("failed count overflow");
+        while let Some(oldest) = self.slots.front().copied() {
+            if index.saturating_sub(oldest.index) <= SLOTS {
+                break;
+            }
+            self.slots.pop_front();
+            self.requests = self.requests.saturating_sub(oldest.requests);
+            self.failed = self.failed.saturating_sub(oldest.failed);
+        }
+
+        let requests = self.requests;
+        let elapsed = at.saturating_duration_since(self.start);
+        if requests < MIN_REQUESTS && elapsed < self.policy.window {
+            return None;
+        }
+        let error_rate = self.failed.to_f64().expect("count fits in f64")
+            / requests.to_f64().expect("count fits in f64");
+        (error_rate > self.policy.error_rate).then_some(AbortReason {
+            error_rate,
+            threshold: self.policy.error_rate,
+            window: self.policy.window,
+            requests,
+            elapsed,
+        })
+    }
+}
+
+/// Watches `outcomes` from the run started at `start` and cancels it once the error rate crosses
+/// the threshold, returning why. Returns `None` if the run ends (every sender gone, or `cancel`
+/// fired elsewhere) first.
+pub(crate) async fn watch(
+    policy: AbortPolicy,
+    start: Instant,
+    mut outcomes: Receiver<HttpRequestOutcome>,
+    cancel: CancellationToken,
+) -> Option<AbortReason> {
+    let mut window = ErrorWindow::new(policy, start);
+    loop {
+        select! {
+            biased;
+            () = cancel.cancelled() => return None,
+            outcome = outcomes.recv() => {
+                let reason = window.record(Instant::now(), outcome?);
+                if reason.is_some() {
+                    cancel.cancel();
+                    return reason;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OK: HttpRequestOutcome = HttpRequestOutcome::SuccessResponse;
+    const ERR: HttpRequestOutcome = HttpRequestOutcome::ServerErrorResponse;
+
+    fn window(error_rate: f64, window_secs: u64) -> (ErrorWindow, Instant) {
+        let start = Instant::now();
+        let policy = AbortPolicy {
+            error_rate,
+            window: Duration::from_secs(window_secs),
+        };
+        (ErrorWindow::new(policy, start), start)
+    }
+
+    fn at(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn waits_for_enough_requests_before_judging() {
+        let (mut window, start) = window(0.5, 10);
+        for i in 0..9 {
+            assert_eq!(window.record(at(start, i), ERR), None);
+        }
+        let reason = window.record(at(start, 9), ERR).unwrap();
+        assert_eq!(reason.requests, 10);
+        assert_eq!(reason.error_rate, 1.0);
+    }
+
+    #[test]
+    fn judges_few_requests_once_a_window_has_passed() {
+        let (mut window, start) = window(0.5, 2);
+        assert_eq!(window.record(at(start, 500), ERR), None);
+        let reason = window.record(at(start, 2_000), ERR).unwrap();
+        assert_eq!(reason.requests, 2);
+        assert_eq!(reason.elapsed, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn aborts_only_above_the_threshold() {
+        let (mut window, start) = window(0.5, 10);
+        for i in 0..10 {
+            let outcome = if i % 2 == 0 { ERR } else { OK };
+            assert_eq!(window.record(at(start, i), outcome), None);
+        }
+        let reason = window.record(at(start, 10), ERR).unwrap();
+        assert_eq!(reason.requests, 11);
+        assert!((reason.error_rate - 6.0 / 11.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn old_outcomes_leave_the_window() {
+        let (mut window, start) = window(0.5, 1);
+        // An early burst of errors, then only successes: the errors age out.
+        for i in 0..5 {
+            window.record(at(start, i), ERR);
+        }
+        for i in 0..20 {
+            assert_eq!(window.record(at(start, 1_100 + i), OK), None);
+        }
+        assert_eq!(window.failed, 0);
+        assert_eq!(window.requests, 20);
+        assert!(window.slots.len() <= 2);
+    }
+
+    #[tokio::test]
+    async fn watch_cancels_the_run() {
+        let (sender, receiver) = tokio::sync::mpsc::channel(MIN_REQUESTS);
+        let cancel = CancellationToken::new();
+        let policy = AbortPolicy {
+            error_rate: 0.1,
+            window: Duration::from_mins(1),
+        };
+        for _ in 0..MIN_REQUESTS {
+            sender.try_send(ERR).unwrap();
+        }
+        let reason = watch(policy, Instant::now(), receiver, cancel.clone())
+            .await
+            .unwrap();
+        assert_eq!(reason.requests, MIN_REQUESTS);
+        assert!(cancel.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn watch_returns_none_when_the_run_ends() {
+        let (sender, receiver) = tokio::sync::mpsc::channel(MIN_REQUESTS);
+        sender.try_send(OK).unwrap();
+        drop(sender);
+        let cancel = CancellationToken::new();
+        let policy = AbortPolicy {
+            error_rate: 0.1,
+            window: Duration::from_mins(1),
+        };
+        assert_eq!(
+            watch(policy, Instant::now(), receiver, cancel.clone()).await,
+            None
+        );
+        assert!(!cancel.is_cancelled());
+    }
+}
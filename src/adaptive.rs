@@ -0,0 +1,207 @@
+//! Adaptive rate control: hold a latency percentile at a target value.
+//!
+//! Workers feed every response time back to the dispatcher. At each adjustment interval, the
+//! controller looks at the chosen percentile of the response times seen since the previous one and
+//! adjusts the rate AIMD-style: it adds a fixed step while the percentile is on target, and halves
+//! the rate when it is over. Requests are still issued as tickets along the arrival process, at
+//! whatever the current rate is.
+
+use std::{
+    sync::{Arc, atomic::AtomicU64},
+    time::Duration,
+};
+
+use tokio::{
+    select,
+    sync::mpsc::{Receiver, Sender},
+    time::Instant,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{arrival::Arrivals, dispatcher::hand_out, histogram::Histogram};
+
+/// Share of the starting rate added at each adjustment while the latency is on target. Also the
+/// floor the rate never drops below, so that the controller keeps getting feedback.
+const INCREASE_FRACTION: f64 = 0.1;
+
+/// Factor applied to the rate when the latency is over target.
+const DECREASE_FACTOR: f64 = 0.5;
+
+/// Adaptive rate control settings (--target-latency).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Adaptive {
+    /// Latency the controlled percentile is held at.
+    pub target_latency: Duration,
+    /// Percentile of the response times that is controlled, as a fraction (e.g. 0.99).
+    pub percentile: f64,
+    /// Time between rate adjustments.
+    pub interval: Duration,
+}
+
+/// AIMD controller for the request rate.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Aimd {
+    settings: Adaptive,
+    rate: f64,
+    step: f64,
+}
+
+impl Aimd {
+    pub(crate) fn new(settings: Adaptive, initial_rate: f64) -> Self {
+        Self {
+            settings,
+            rate: initial_rate,
+            step: initial_rate * INCREASE_FRACTION,
+        }
+    }
+
+    pub(crate) fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Adjusts the rate from the response times seen over the last interval, then clears them. With
+    /// no response in the interval, the rate is held.
+    pub(crate) fn adjust(&mut self, samples: &mut Histogram) -> f64 {
+        if !samples.is_empty() {
+            let observed = Duration::from_nanos(samples.quantile(self.settings.percentile));
+            self.rate = if observed > self.settings.target_latency {
+                (self.rate * DECREASE_FACTOR).max(self.step)
+            } else {
+                self.rate + self.step
+            };
+        }
+        samples.take();
+        self.rate
+    }
+}
+
+/// Issues tickets to `workers` for `duration` at the controller's current rate, adjusting it from
+/// `feedback` every interval, and counts the tickets no worker had room for in `dropped`. Returns
+/// the chosen rate over time, as `(elapsed, rate)` points.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "the dispatcher's inputs plus the controller's"
+)]
+pub(crate) async fn run(
+    mut aimd: Aimd,
+    mut arrivals: Arrivals,
+    duration: Duration,
+    start: Instant,
+    workers: Vec<Sender<Instant>>,
+    dropped: Arc<AtomicU64>,
+    mut feedback: Receiver<Duration>,
+    cancel: CancellationToken,
+) -> Vec<(Duration, f64)> {
+    let end = start.checked_add(duration).expect("run end overflow");
+    let interval = aimd.settings.interval;
+    let mut adjustments = tokio::time::interval_at(
+        start
+            .checked_add(interval)
+            .expect("adjustment time overflow"),
+        interval,
+    );
+    let mut rate_points = vec![(Duration::ZERO, aimd.rate())];
+    // Response times since the last adjustment, in a histogram so that memory does not grow with the
+    // request rate.
+    let mut samples = Histogram::default();
+    let mut next_worker: usize = 0;
+    // Request units called for up to `since`; they accrue at the current rate from there.
+    let mut units = 0.0_f64;
+    let mut since = start;
+
+    loop {
+        let wait = (arrivals.next_due() - units).max(0.0_f64) / aimd.rate();
+        let send_at = since.checked_add(Duration::try_from_secs_f64(wait).unwrap_or(duration));
+        select! {
+            () = cancel.cancelled() => break,
+            () = tokio::time::sleep_until(end) => break,
+            () = tokio::time::sleep_until(send_at.unwrap_or(end)), if send_at.is_some_and(|at| at < end) => {
+                let Some(scheduled) = send_at else {
+                    continue;
+                };
+                if !hand_out(&workers, &mut next_worker, scheduled, &dropped) {
+                    break;
+                }
+                arrivals.advance();
+            }
+            Some(response_time) = feedback.recv() => samples.record_duration(response_time),
+            now = adjustments.tick() => {
+                units += aimd.rate() * now.saturating_duration_since(since).as_secs_f64();
+                since = now;
+                let elapsed = now.saturating_duration_since(start);
+                rate_points.push((elapsed, aimd.rate()));
+                rate_points.push((elapsed, aimd.adjust(&mut samples)));
+            }
+        }
+    }
+    let elapsed = Instant::now()
+        .saturating_duration_since(start)
+        .min(duration);
+    rate_points.push((elapsed, aimd.rate()));
+    rate_points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aimd(target_ms: u64) -> Aimd {
+        Aimd::new(
+            Adaptive {
+                target_latency: Duration::from_millis(target_ms),
+                percentile: 0.99,
+                interval: Duration::from_secs(1),
+            },
+            100.0,
+        )
+    }
+
+    fn ms(values: &[u64]) -> Histogram {
+        let mut histogram = Histogram::default();
+        for &value in values {
+            histogram.record_duration(Duration::from_millis(value));
+        }
+        histogram
+    }
+
+    #[test]
+    fn increases_additively_while_on_target() {
+        let mut aimd = aimd(50);
+        assert_eq!(aimd.adjust(&mut ms(&[10, 20, 30])), 110.0);
+        assert_eq!(aimd.adjust(&mut ms(&[10, 20, 30])), 120.0);
+    }
+
+    #[test]
+    fn halves_when_over_target() {
+        let mut aimd = aimd(50);
+        assert_eq!(aimd.adjust(&mut ms(&[10, 20, 80])), 50.0);
+        assert_eq!(aimd.adjust(&mut ms(&[80])), 25.0);
+    }
+
+    #[test]
+    fn never_drops_below_one_step() {
+        let mut aimd = aimd(50);
+        for _ in 0..10 {
+            aimd.adjust(&mut ms(&[500]));
+        }
+        assert_eq!(aimd.rate(), 10.0);
+    }
+
+    #[test]
+    fn holds_without_feedback_and_clears_samples() {
+        let mut aimd = aimd(50);
+        let mut samples = ms(&[10]);
+        assert_eq!(aimd.adjust(&mut samples), 110.0);
+        assert!(samples.is_empty());
+        assert_eq!(aimd.adjust(&mut samples), 110.0);
+    }
+
+    #[test]
+    fn controls_the_chosen_percentile() {
+        let mut p50 = aimd(50);
+        p50.settings.percentile = 0.5;
+        // The median is on target even though the tail is not.
+        assert_eq!(p50.adjust(&mut ms(&[10, 20, 30, 400])), 110.0);
+    }
+}
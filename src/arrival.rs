@@ -0,0 +1,99 @@
+//! Arrival processes for the open-loop schedule.
+//!
+//! Arrivals are generated in "request units" rather than seconds: an arrival at `n` is due once the
+//! rate profile has called for `n` requests since the start of the run. Time-changing a unit-rate
+//! process this way keeps it constant, Poisson or uniform at any rate, so ramps and stages work
+//! unchanged for every process.
+
+use rand::rngs::StdRng;
+use rand::{Rng as _, SeedableRng as _};
+
+use crate::cli::ArrivalProcess;
+
+/// Arrival times of the run's requests, one request unit apart on average.
+pub(crate) struct Arrivals {
+    process: ArrivalProcess,
+    rng: StdRng,
+    next: f64,
+}
+
+impl Arrivals {
+    /// Arrivals following `process`; the random processes draw from a stream seeded with `seed`.
+    /// The first constant arrival is due at the start of the run.
+    pub(crate) fn new(process: ArrivalProcess, seed: u64) -> Self {
+        let mut arrivals = Self {
+            process,
+            rng: StdRng::seed_from_u64(seed),
+            next: 0.0_f64,
+        };
+        arrivals.next = match process {
+            ArrivalProcess::Constant => 0.0_f64,
+            ArrivalProcess::Poisson | ArrivalProcess::Uniform => arrivals.gap(),
+        };
+        arrivals
+    }
+
+    /// Number of requests the rate profile must have called for before the next arrival is due.
+    pub(crate) fn next_due(&self) -> f64 {
+        self.next
+    }
+
+    /// Moves on to the following arrival.
+    pub(crate) fn advance(&mut self) {
+        self.next += self.gap();
+    }
+
+    fn gap(&mut self) -> f64 {
+        match self.process {
+            ArrivalProcess::Constant => 1.0_f64,
+            ArrivalProcess::Poisson => {
+                // Inverse transform sampling of an exponential; `1 - u` is in (0, 1] so ln is finite.
+                let u: f64 = self.rng.random();
+                -(1.0_f64 - u).ln()
+            }
+            ArrivalProcess::Uniform => self.rng.random_range(0.0_f64..2.0_f64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(arrivals: &mut Arrivals, n: usize) -> Vec<f64> {
+        std::iter::repeat_with(|| {
+            let due = arrivals.next_due();
+            arrivals.advance();
+            due
+        })
+        .take(n)
+        .collect()
+    }
+
+    #[test]
+    fn constant_arrivals_are_one_request_apart() {
+        let mut arrivals = Arrivals::new(ArrivalProcess::Constant, 0);
+        assert_eq!(take(&mut arrivals, 4), vec![0.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn random_arrivals_are_reproducible_from_seed() {
+        for process in [ArrivalProcess::Poisson, ArrivalProcess::Uniform] {
+            let a = take(&mut Arrivals::new(process, 42), 50);
+            let b = take(&mut Arrivals::new(process, 42), 50);
+            let other_seed = take(&mut Arrivals::new(process, 43), 50);
+            assert_eq!(a, b);
+            assert_ne!(a, other_seed);
+            assert!(a.windows(2).all(|w| w[0] <= w[1]));
+        }
+    }
+
+    #[test]
+    fn random_arrivals_keep_the_mean_rate() {
+        for process in [ArrivalProcess::Poisson, ArrivalProcess::Uniform] {
+            let mut arrivals = Arrivals::new(process, 7);
+            let last = *take(&mut arrivals, 100_000).last().unwrap();
+            assert!((last / 100_000.0 - 1.0).abs() < 0.02, "{process:?}: {last}");
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! Response assertions (--expect-status, --expect-header, --expect-body-contains,
+//! --expect-body-regex, --expect-json).
+//!
+//! Every full response is checked against all assertions. A 2xx that fails any of them counts as an
+//! assertion failure instead of a success, so that e.g. a 200 carrying an error page is not one;
+//! other responses keep the class of their status code, and each failed assertion is counted.
+//!
+//! Body text is searched for as the body streams in; regexes and JSON need the whole body, which
+//! is kept up to --expect-body-max-bytes and fails them when longer.
+
+use std::{cell::OnceCell, fmt};
+
+use http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use memchr::memmem::Finder;
+use regex::bytes::Regex;
+
+/// A check every response must pass.
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub enum Assertion {
+    /// The status code is one of these.
+    Status(Vec<u16>),
+    /// The header is present, with exactly this value if one is given.
+    Header {
+        name: HeaderName,
+        value: Option<HeaderValue>,
+    },
+    /// The body contains this text.
+    BodyContains(String),
+    /// The body matches this regular expression.
+    BodyRegex(Regex),
+    /// The body is JSON with this value at this JSON pointer (RFC 6901).
+    Json {
+        pointer: String,
+        value: serde_json::Value,
+    },
+}
+
+impl Assertion {
+    /// Whether checking the assertion needs the whole response body in memory.
+    fn needs_whole_body(&self) -> bool {
+        matches!(*self, Self::BodyRegex(_) | Self::Json { .. })
+    }
+
+    /// Whether the response passes, the assertion being `assertions[index]`. `json` caches the
+    /// parsed body across assertions.
+    fn passes(
+        &self,
+        index: usize,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &BodyCheck,
+        json: &OnceCell<Option<serde_json::Value>>,
+    ) -> bool {
+        match *self {
+            Self::Status(ref codes) => codes.contains(&status.as_u16()),
+            Self::Header {
+                ref name,
+                ref value,
+            } => headers
+                .get_all(name)
+                .iter()
+                .any(|actual| value.as_ref().is_none_or(|value| actual == value)),
+            Self::BodyContains(_) => body.found(index),
+            Self::BodyRegex(ref regex) => body.whole().is_some_and(|body| regex.is_match(body)),
+            Self::Json {
+                ref pointer,
+                ref value,
+            } => json
+                .get_or_init(|| serde_json::from_slice(body.whole()?).ok())
+                .as_ref()
+                .and_then(|json| json.pointer(pointer))
+                .is_some_and(|actual| actual == value),
+        }
+    }
+}
+
+impl fmt::Display for Assertion {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Status(ref codes) => {
+                let codes: Vec<String> = codes.iter().map(ToString::to_string).collect();
+                write!(f, "status in {}", codes.join(","))
+            }
+            Self::Header {
+                ref name,
+                value: Some(ref value),
+            } => write!(
+                f,
+                "header {name}: {}",
+                value.to_str().unwrap_or("<invalid>")
+            ),
+            Self::Header {
+                ref name,
+                value: None,
+            } => write!(f, "header {name} present"),
+            Self::BodyContains(ref text) => write!(f, "body contains \"{text}\""),
+            Self::BodyRegex(ref regex) => write!(f, "body matches /{regex}/"),
+            Self::Json {
+                ref pointer,
+                ref value,
+            } => write!(f, "JSON {pointer} == {value}"),
+        }
+    }
+}
+
+/// What the body assertions need from a response body, fed chunk by chunk as it streams in.
+pub(crate) struct BodyCheck {
+    /// Per assertion: for --expect-body-contains, its searcher and whether it found its text.
+    searches: Vec<Option<(Finder<'static>, bool)>>,
+    /// The last bytes fed, so that a text split across two chunks is still found.
+    tail: Vec<u8>,
+    /// Bytes of `tail` to keep: the longest searched text, minus one.
+    overlap: usize,
+    /// The body so far if a regex or JSON assertion needs it; `None` once it outgrew `limit`.
+    body: Option<Vec<u8>>,
+    limit: usize,
+    /// Whether the body outgrew `limit` while a regex or JSON assertion needed it.
+    overflowed: bool,
+}
+
+impl BodyCheck {
+    /// Checks for `assertions`, keeping at most `limit` bytes of body for the regex and JSON ones.
+    pub(crate) fn new(assertions: &[Assertion], limit: usize) -> Self {
+        let searches: Vec<_> = assertions
+            .iter()
+            .map(|assertion| match *assertion {
+                Assertion::BodyContains(ref text) => {
+                    Some((Finder::new(text.as_bytes()).into_owned(), text.is_empty()))
+                }
+                Assertion::Status(_)
+                | Assertion::Header { .. }
+                | Assertion::BodyRegex(_)
+                | Assertion::Json { .. } => None,
+            })
+            .collect();
+        let overlap = searches
+            .iter()
+            .flatten()
+            .map(|search| search.0.needle().len().saturating_sub(1))
+            .max()
+            .unwrap_or(0);
+        Self {
+            searches,
+            tail: Vec::new(),
+            overlap,
+            body: assertions
+                .iter()
+                .any(Assertion::needs_whole_body)
+                .then(Vec::new),
+            limit,
+            overflowed: false,
+        }
+    }
+
+    /// Feeds the next chunk of the body.
+    pub(crate) fn feed(&mut self, chunk: &[u8]) {
+        for &mut (ref finder, ref mut found) in self.searches.iter_mut().flatten() {
+            if *found {
+                continue;
+            }
+            // Either the text lies within the chunk, or it straddles the chunk's start.
+            let straddle = finder.needle().len().saturating_sub(1);
+            let before = self
+                .tail
+                .get(self.tail.len().saturating_sub(straddle)..)
+                .unwrap_or_default();
+            let after = chunk.get(..straddle).unwrap_or(chunk);
+            *found =
+                finder.find(chunk).is_some() || finder.find(&[before, after].concat()).is_some();
+        }
+        if self.overlap > 0 {
+            self.tail.extend_from_slice(chunk);
+            let excess = self.tail.len().saturating_sub(self.overlap);
+            self.tail.drain(..excess);
+        }
+        if let Some(ref mut body) = self.body {
+            if body.len().saturating_add(chunk.len()) > self.limit {
+                self.body = None;
+                self.overflowed = true;
+            } else {
+                body.extend_from_slice(chunk);
+            }
+        }
+    }
+
+    /// Whether the body was too long for the regex and JSON assertions, which it then fails.
+    pub(crate) fn over_limit(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Whether the text of assertion `index` (--expect-body-contains) was found.
+    fn found(&self, index: usize) -> bool {
+        self.searches
+            .get(index)
+            .and_then(Option::as_ref)
+            .is_some_and(|&(_, found)| found)
+    }
+
+    /// The whole body, unless it outgrew the limit (or no assertion needed it).
+    fn whole(&self) -> Option<&[u8]> {
+        self.body.as_deref()
+    }
+}
+
+/// Indexes (into `assertions`) of the assertions a response fails, its body having been fed to
+/// `body`.
+pub(crate) fn failures(
+    assertions: &[Assertion],
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: &BodyCheck,
+) -> Vec<usize> {
+    // Parsed once, and only if a JSON assertion needs it; `None` if the body is not JSON.
+    let json = OnceCell::new();
+    assertions
+        .iter()
+        .enumerate()
+        .filter(|&(index, assertion)| !assertion.passes(index, status, headers, body, &json))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(assertions: &[Assertion], status: u16, body: &str) -> Vec<usize> {
+        check_chunks(assertions, status, &[body], usize::MAX)
+    }
+
+    /// Like `check`, with the body arriving in `chunks` and at most `limit` bytes of it kept.
+    fn check_chunks(
+        assertions: &[Assertion],
+        status: u16,
+        chunks: &[&str],
+        limit: usize,
+    ) -> Vec<usize> {
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        let mut body = BodyCheck::new(assertions, limit);
+        for chunk in chunks {
+            body.feed(chunk.as_bytes());
+        }
+        failures(
+            assertions,
+            StatusCode::from_u16(status).unwrap(),
+            &headers,
+            &body,
+        )
+    }
+
+    #[test]
+    fn status_must_be_listed() {
+        let assertions = [Assertion::Status(vec![200, 204])];
+        assert!(check(&assertions, 204, "").is_empty());
+        assert_eq!(check(&assertions, 500, ""), vec![0]);
+    }
+
+    #[test]
+    fn header_presence_and_value() {
+        let assertions = [
+            Assertion::Header {
+                name: HeaderName::from_static("content-type"),
+                value: None,
+            },
+            Assertion::Header {
+                name: HeaderName::from_static("content-type"),
+                value: Some(HeaderValue::from_static("text/html")),
+            },
+            Assertion::Header {
+                name: HeaderName::from_static("x-missing"),
+                value: None,
+            },
+        ];
+        assert_eq!(check(&assertions, 200, ""), vec![1, 2]);
+    }
+
+    #[test]
+    fn body_contains_and_regex() {
+        let assertions = [
+            Assertion::BodyContains("\"ok\"".to_owned()),
+            Assertion::BodyRegex(Regex::new(r"id: \d+").unwrap()),
+        ];
+        assert!(check(&assertions, 200, "{\"ok\"} id: 42").is_empty());
+        assert_eq!(check(&assertions, 200, "<html>error</html>"), vec![0, 1]);
+    }
+
+    #[test]
+    fn body_text_split_across_chunks_is_found() {
+        let assertions = [
+            Assertion::BodyContains("welcome".to_owned()),
+            Assertion::BodyContains("x".to_owned()),
+            Assertion::BodyContains(String::new()),
+            Assertion::BodyRegex(Regex::new("wel+come").unwrap()),
+        ];
+        assert!(check_chunks(&assertions, 200, &["xx wel", "c", "ome"], usize::MAX).is_empty());
+        assert_eq!(
+            check_chunks(&assertions, 200, &["wel", " ", "come"], usize::MAX),
+            vec![0, 1, 3]
+        );
+    }
+
+    #[test]
+    fn body_over_the_limit_fails_regex_and_json_but_not_contains() {
+        let assertions = [
+            Assertion::BodyContains("end".to_owned()),
+            Assertion::BodyRegex(Regex::new("start").unwrap()),
+            Assertion::Json {
+                pointer: "/ok".to_owned(),
+                value: serde_json::json!(true),
+            },
+        ];
+        let mut body = BodyCheck::new(&assertions, 16);
+        body.feed(br#"{"ok": true, "#);
+        assert!(!body.over_limit());
+        body.feed(br#""note": "start ... end"}"#);
+        assert!(body.over_limit());
+        let failed = failures(&assertions, StatusCode::OK, &HeaderMap::new(), &body);
+        assert_eq!(failed, vec![1, 2]);
+        assert!(check_chunks(&assertions[1..], 200, &[r#"{"ok": true}"#], 16).contains(&0));
+        assert!(check_chunks(&assertions[2..], 200, &[r#"{"ok": true}"#], 16).is_empty());
+    }
+
+    #[test]
+    fn json_pointer_equality() {
+        let assertions = [
+            Assertion::Json {
+                pointer: "/status".to_owned(),
+                value: serde_json::json!("ok"),
+            },
+            Assertion::Json {
+                pointer: "/items/1/count".to_owned(),
+                value: serde_json::json!(3),
+            },
+        ];
+        let body = r#"{"status": "ok", "items": [{"count": 1}, {"count": 3}]}"#;
+        assert!(check(&assertions, 200, body).is_empty());
+        assert_eq!(
+            check(&assertions, 200, r#"{"status": "degraded"}"#),
+            vec![0, 1]
+        );
+        // A body that is not JSON fails every JSON assertion.
+        assert_eq!(check(&assertions, 200, "<html>"), vec![0, 1]);
+    }
+
+    #[test]
+    fn descriptions() {
+        let assertions = [
+            Assertion::Status(vec![200, 204]),
+            Assertion::Header {
+                name: HeaderName::from_static("x-id"),
+                value: None,
+            },
+            Assertion::BodyRegex(Regex::new("a+b").unwrap()),
+            Assertion::Json {
+                pointer: "/ok".to_owned(),
+                value: serde_json::json!(true),
+            },
+        ];
+        let described: Vec<String> = assertions.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            described,
+            [
+                "status in 200,204",
+                "header x-id present",
+                "body matches /a+b/",
+                "JSON /ok == true"
+            ]
+        );
+    }
+}
@@ -1,29 +1,32 @@
 //! Cumulative distribution function (CDF) for latency percentiles.
 //!
-//! Computes percentile points over a sorted list of latencies using a
+//! Computes percentile points over a histogram of latencies using a
 //! log-spaced tail (e.g. p50, p90, p99, p99.9).
 
 use std::f64::consts::LN_10;
-use std::time::Duration;
 
-/// Compute CDF percentiles for a sorted list of latencies.
+use crate::histogram::Histogram;
+
+/// Compute CDF percentiles for a histogram of latencies (or of any other values, such as response
+/// sizes).
 ///
 /// Parameters:
 /// - `start`: initial tail value (e.g. 1.0 for 100th percentile).
 /// - `resolution`: number of steps per decade (e.g. 16).
 /// - `steps`: number of decades to cover (e.g. 4 → 1.0 down to 0.0001).
-/// - `durations`: latencies; will be sorted in place.
+/// - `histogram`: the values.
 ///
-/// Returns `(percentile, duration)` pairs with percentile in [0, 1].
-pub(crate) fn calculate_cdf<'a>(
+/// Returns `(percentile, value)` pairs with percentile in [0, 1]. Each value is its histogram
+/// bucket's, as [`Histogram::buckets`] gives it: exact unless the bucket held different values.
+pub(crate) fn calculate_cdf(
     start: f64,
     resolution: u64,
     steps: u64,
-    durations: impl Iterator<Item = &'a Duration>,
-) -> Vec<(f64, Duration)> {
-    let mut durations = durations.copied().collect::<Vec<_>>();
-    durations.sort();
-
+    histogram: &Histogram,
+) -> Vec<(f64, u64)> {
+    if histogram.is_empty() {
+        return Vec::new();
+    }
     let resolution_f64 =
         f64::from(u32::try_from(resolution).expect("resolution fits in u32 for CDF"));
     let k = -LN_10 / resolution_f64;
@@ -36,21 +39,32 @@ pub(crate) fn calculate_cdf<'a>(
     let n_usize = usize::try_from(n).expect("CDF step count fits in usize");
     let mut result = Vec::with_capacity(n_usize);
 
-    let len = durations.len();
+    let len = histogram.len();
+    let mut buckets = histogram.buckets();
+    // Value of the current bucket, and number of values up to and including it.
+    let mut value = 0;
+    let mut through: u64 = 0;
     for _ in 0..=n {
         let p = 1.0_f64 - r;
         let p_scaled = (p * 1e9).round().clamp(0.0, 1e9);
         let p_scaled = format!("{p_scaled:.0}")
             .parse::<u64>()
             .expect("p_scaled in 0..=1e9 parses as u64");
-        let idx = u64::try_from(len)
-            .expect("len fits in u64")
-            .checked_mul(p_scaled)
+        let idx = u128::from(len)
+            .checked_mul(u128::from(p_scaled))
             .and_then(|v| v.checked_div(1_000_000_000))
+            .and_then(|v| u64::try_from(v).ok())
             .expect("CDF index overflow");
-        let idx = usize::try_from(idx).expect("CDF index fits in usize");
-        if let Some(value) = durations.get(idx) {
-            result.push((p, *value));
+        // The value of (0-based) rank `idx` is in the first bucket that takes the count past it.
+        while through <= idx {
+            let Some((bucket, count)) = buckets.next() else {
+                break;
+            };
+            value = bucket;
+            through = through.checked_add(count).expect("CDF count overflow");
+        }
+        if through > idx {
+            result.push((p, value));
         }
         r *= ratio;
     }
@@ -60,17 +74,36 @@ pub(crate) fn calculate_cdf<'a>(
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
 
     fn ns(n: u64) -> Duration {
         Duration::from_nanos(n)
     }
 
+    /// CDF of `latencies`, recorded into a histogram.
+    fn cdf(
+        start: f64,
+        resolution: u64,
+        steps: u64,
+        latencies: &[Duration],
+    ) -> Vec<(f64, Duration)> {
+        let mut histogram = Histogram::default();
+        for &latency in latencies {
+            histogram.record_duration(latency);
+        }
+        calculate_cdf(start, resolution, steps, &histogram)
+            .into_iter()
+            .map(|(p, nanos)| (p, ns(nanos)))
+            .collect()
+    }
+
     /// Empty input produces empty CDF.
     #[test]
     fn empty_input() {
         let input: Vec<Duration> = vec![];
-        let got = calculate_cdf(1.0, 16, 4, input.iter());
+        let got = cdf(1.0, 16, 4, &input);
         let expected: Vec<(f64, Duration)> = vec![];
         assert_eq!(got, expected);
     }
@@ -79,11 +112,11 @@ mod tests {
     #[test]
     fn single_latency() {
         let input = vec![ns(100)];
-        let got = calculate_cdf(1.0, 16, 4, input.iter());
+        let got = cdf(1.0, 16, 4, &input);
         assert!(!got.is_empty());
         assert!(
             got.iter()
-                .all(|(p, d)| (*p >= 0.0 && *p <= 1.0) && *d == ns(100))
+                .all(|&(p, d)| (0.0..=1.0).contains(&p) && d == ns(100))
         );
     }
 
@@ -91,9 +124,9 @@ mod tests {
     #[test]
     fn two_latencies_hardcoded() {
         let input = vec![ns(10), ns(20)];
-        let got = calculate_cdf(1.0, 2, 2, input.iter());
+        let got = cdf(1.0, 2, 2, &input);
         // n=4, 5 steps. ratio=10^(-1/2). p = 1 - r with r = 1, ratio, ratio^2, ratio^3, ratio^4.
-        let expected = vec![
+        let expected = [
             (0.0, ns(10)),
             (0.683_772_233_983_162, ns(20)),
             (0.9, ns(20)),
@@ -101,15 +134,12 @@ mod tests {
             (0.99, ns(20)),
         ];
         assert_eq!(got.len(), expected.len(), "length mismatch");
-        for (i, ((p_got, d_got), (p_exp, d_exp))) in got.iter().zip(expected.iter()).enumerate() {
+        for (i, (&(p_got, d_got), &(p_exp, d_exp))) in got.iter().zip(expected.iter()).enumerate() {
             assert!(
-                (*p_got - p_exp).abs() < 1e-9,
-                "index {}: percentile {} vs {}",
-                i,
-                p_got,
-                p_exp
+                (p_got - p_exp).abs() < 1e-9,
+                "index {i}: percentile {p_got} vs {p_exp}"
             );
-            assert_eq!(d_got, d_exp, "index {}: duration mismatch", i);
+            assert_eq!(d_got, d_exp, "index {i}: duration mismatch");
         }
     }
 
@@ -117,8 +147,8 @@ mod tests {
     #[test]
     fn three_latencies_hardcoded() {
         let input = vec![ns(100), ns(200), ns(300)];
-        let got = calculate_cdf(1.0, 2, 2, input.iter());
-        let expected = vec![
+        let got = cdf(1.0, 2, 2, &input);
+        let expected = [
             (0.0, ns(100)),
             (0.683_772_233_983_162, ns(300)),
             (0.9, ns(300)),
@@ -126,15 +156,12 @@ mod tests {
             (0.99, ns(300)),
         ];
         assert_eq!(got.len(), expected.len(), "length mismatch");
-        for (i, ((p_got, d_got), (p_exp, d_exp))) in got.iter().zip(expected.iter()).enumerate() {
+        for (i, (&(p_got, d_got), &(p_exp, d_exp))) in got.iter().zip(expected.iter()).enumerate() {
             assert!(
-                (*p_got - p_exp).abs() < 1e-9,
-                "index {}: percentile {} vs {}",
-                i,
-                p_got,
-                p_exp
+                (p_got - p_exp).abs() < 1e-9,
+                "index {i}: percentile {p_got} vs {p_exp}"
             );
-            assert_eq!(d_got, d_exp, "index {}: duration mismatch", i);
+            assert_eq!(d_got, d_exp, "index {i}: duration mismatch");
         }
     }
 
@@ -142,10 +169,10 @@ mod tests {
     #[test]
     fn unsorted_input_sorted_by_cdf() {
         let input = vec![ns(300), ns(100), ns(200)];
-        let got = calculate_cdf(1.0, 2, 2, input.iter());
-        let sorted_latencies: Vec<u64> = got.iter().map(|(_, d)| d.as_nanos() as u64).collect();
+        let got = cdf(1.0, 2, 2, &input);
+        let sorted_latencies: Vec<u64> = got.iter().map(|&(_, d)| d.as_nanos() as u64).collect();
         let mut sorted = sorted_latencies.clone();
-        sorted.sort();
+        sorted.sort_unstable();
         assert_eq!(sorted_latencies, sorted);
     }
 
@@ -153,13 +180,12 @@ mod tests {
     #[test]
     fn output_latencies_non_decreasing() {
         let input: Vec<Duration> = (0..100).map(ns).collect();
-        let got = calculate_cdf(1.0, 4, 2, input.iter());
-        let latencies: Vec<u64> = got.iter().map(|(_, d)| d.as_nanos() as u64).collect();
+        let got = cdf(1.0, 4, 2, &input);
+        let latencies: Vec<u64> = got.iter().map(|&(_, d)| d.as_nanos() as u64).collect();
         for w in latencies.windows(2) {
             assert!(
                 w[0] <= w[1],
-                "latencies should be non-decreasing: {:?}",
-                latencies
+                "latencies should be non-decreasing: {latencies:?}"
             );
         }
     }
@@ -168,17 +194,15 @@ mod tests {
     #[test]
     fn percentiles_in_valid_range() {
         let input: Vec<Duration> = (0..50).map(|i| ns(i * 10)).collect();
-        let got = calculate_cdf(1.0, 8, 3, input.iter());
+        let got = cdf(1.0, 8, 3, &input);
         let mut prev_p = -1.0_f64;
-        for (p, _) in &got {
-            assert!(*p >= 0.0 && *p <= 1.0, "percentile {} out of range", p);
+        for &(p, _) in &got {
+            assert!((0.0..=1.0).contains(&p), "percentile {p} out of range");
             assert!(
-                *p >= prev_p,
-                "percentiles should be non-decreasing: {} then {}",
-                prev_p,
-                p
+                p >= prev_p,
+                "percentiles should be non-decreasing: {prev_p} then {p}"
             );
-            prev_p = *p;
+            prev_p = p;
         }
     }
 }
@@ -1,18 +1,25 @@
 //! CLI argument parsing. Uses clap internally and exposes a simple struct.
 
 use bytes::Bytes;
-use clap::Parser;
 use clap::builder::styling::{AnsiColor, Styles};
 use clap::value_parser;
+use clap::{ArgGroup, Parser, ValueEnum};
 use reqwest::Identity;
 use reqwest::tls::Certificate;
+use rust_decimal::prelude::ToPrimitive as _;
 use std::path::PathBuf;
 use std::str::FromStr as _;
 use std::time::Duration;
 use tokio::fs::{self, File};
 use tokio::io::AsyncReadExt as _;
 
+use crate::abort::AbortPolicy;
+use crate::adaptive::Adaptive;
+use crate::assertion::Assertion;
 use crate::error::{AppError, Result};
+use crate::histogram;
+use crate::search::Search;
+use crate::stages::{Stage, StageTarget, parse_stages};
 
 /// Colored help styling (clap v4 best practice: explicit styles for help/errors).
 const STYLES: Styles = Styles::styled()
@@ -26,6 +33,9 @@ const STYLES: Styles = Styles::styled()
     .context(AnsiColor::Cyan.on_default().dimmed())
     .context_value(AnsiColor::Cyan.on_default().italic());
 
+/// Shortest --snapshot-interval: every worker snapshots its counters and latencies this often.
+const MIN_SNAPSHOT_INTERVAL: Duration = Duration::from_millis(10);
+
 /// Parsed CLI arguments (no clap dependency at the type level).
 #[non_exhaustive]
 #[derive(Debug, Clone)]
@@ -39,13 +49,64 @@ pub struct Args {
     pub key: Option<PathBuf>,
     pub location: bool,
     pub requests_per_second: rust_decimal::Decimal,
+    /// Number of closed-loop virtual users; `None` runs the open-loop rate schedule.
+    pub concurrency: Option<usize>,
+    /// Pause each virtual user takes between a response and its next request.
+    pub think_time: Option<Duration>,
+    /// Time to ramp the target rate linearly from 0 up to `requests_per_second`.
+    pub ramp_up: Option<Duration>,
+    /// Time to ramp the target rate linearly back down to 0 at the end of the run.
+    pub ramp_down: Option<Duration>,
+    /// Stages from --stages, run back to back; overrides the rate/concurrency and duration.
+    pub stages: Option<Vec<Stage>>,
+    /// How open-loop requests are spread over time.
+    pub arrival: ArrivalProcess,
+    /// Seed for randomized arrivals (chosen at random when --seed is not given).
+    pub seed: u64,
+    /// Cap on requests in flight across all workers; scheduled requests beyond it are dropped.
+    pub max_in_flight: Option<usize>,
+    /// Number of scheduler workers (default: one per runtime thread).
+    pub workers: Option<usize>,
+    /// Number of Tokio runtime worker threads (default: one per CPU core).
+    pub threads: Option<usize>,
+    /// Initial part of the run whose requests are sent but left out of the latency distributions.
+    pub warmup: Option<Duration>,
+    /// Total number of requests to send (-n/--requests); the run ends once they are sent instead of
+    /// after a fixed time.
+    pub requests: Option<u64>,
+    /// Search for the highest request rate meeting these SLOs, with probe runs of `duration` each
+    /// starting at `requests_per_second`, instead of running at a fixed rate.
+    pub search: Option<Search>,
+    /// Adjust the request rate, starting from `requests_per_second`, to hold a latency percentile
+    /// at a target instead of running at a fixed rate.
+    pub adaptive: Option<Adaptive>,
+    /// Length of the run. With --requests: the time the target rate takes to send them (open loop),
+    /// or zero (closed loop, bounded by the request count alone).
     pub duration: Duration,
+    /// How long to wait for in-flight requests once the run is over, interrupted or not; those still
+    /// running after it are abandoned (default: wait for all of them, or 5 seconds after an
+    /// interruption).
+    pub drain_timeout: Option<Duration>,
+    /// Stop the run early once too many requests fail (--abort-on-error-rate).
+    pub abort: Option<AbortPolicy>,
+    /// Checks every response must pass (--expect-*); a 2xx failing any of them is an assertion
+    /// failure.
+    pub assertions: Vec<Assertion>,
+    /// Most body bytes kept for the regex and JSON assertions; a longer body fails them.
+    pub expect_body_max_bytes: usize,
     /// Per-request timeout (e.g. curl's -m/--max-time).
     pub max_time: Option<Duration>,
     /// Connection timeout (seconds, decimal allowed).
     pub connect_timeout: Option<Duration>,
     /// Path to write the protobuf run report.
     pub output: PathBuf,
+    /// Path to stream one length-delimited `RequestResult` per request to (--events).
+    pub events: Option<PathBuf>,
+    /// Significant decimal digits kept by the latency and size histograms (--histogram-precision).
+    pub histogram_precision: u8,
+    /// Time between worker snapshots, each of which adds a point of latency percentiles over time
+    /// (--snapshot-interval).
+    pub snapshot_interval: Duration,
     pub protocol: HttpProtocol,
     pub payload: Option<Payload>,
     pub identity: Option<Identity>,
@@ -74,6 +135,29 @@ impl AsRef<str> for HttpProtocol {
     }
 }
 
+/// Distribution of the gaps between consecutive open-loop requests.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrivalProcess {
+    /// Evenly spaced requests.
+    Constant,
+    /// Exponentially distributed gaps (a Poisson process).
+    Poisson,
+    /// Gaps drawn uniformly between zero and twice the mean gap.
+    Uniform,
+}
+
+impl AsRef<str> for ArrivalProcess {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        match *self {
+            ArrivalProcess::Constant => "constant",
+            ArrivalProcess::Poisson => "poisson",
+            ArrivalProcess::Uniform => "uniform",
+        }
+    }
+}
+
 #[non_exhaustive]
 #[derive(Debug, Clone)]
 pub enum Payload {
@@ -93,6 +177,26 @@ struct CliProtocol {
     http2_prior_knowledge: bool,
 }
 
+/// Arrival process values accepted by --arrival.
+#[derive(ValueEnum, Debug, Clone, Copy, Default)]
+enum CliArrival {
+    #[default]
+    Constant,
+    Poisson,
+    Uniform,
+}
+
+impl From<CliArrival> for ArrivalProcess {
+    #[inline]
+    fn from(arrival: CliArrival) -> Self {
+        match arrival {
+            CliArrival::Constant => ArrivalProcess::Constant,
+            CliArrival::Poisson => ArrivalProcess::Poisson,
+            CliArrival::Uniform => ArrivalProcess::Uniform,
+        }
+    }
+}
+
 /// Load test CLI - HTTP client with curl-like options.
 #[derive(Parser, Debug)]
 #[command(name = "loadtest")]
@@ -101,6 +205,12 @@ struct CliProtocol {
 #[command(styles = STYLES)]
 #[command(next_line_help = true)]
 #[command(arg_required_else_help = true)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "each bool is a command-line switch"
+)]
+#[command(group = ArgGroup::new("closed_loop").args(["concurrency", "stages"]))]
+#[command(group = ArgGroup::new("slo").args(["slo_p99_secs", "slo_error_rate"]).multiple(true))]
 struct Cli {
     /// URL to request (e.g. <https://example.com>).
     #[arg(required = true)]
@@ -142,14 +252,146 @@ struct Cli {
     #[arg(short = 'L', long = "location")]
     location: bool,
 
+    /// Fail responses whose status code is not one of these (comma-separated, e.g. 200,204). For
+    /// this and the other --expect-* checks, only a 2xx that fails one counts as an assertion
+    /// failure; any other response keeps its status class (e.g. a 503 stays a server error).
+    #[arg(long = "expect-status", value_name = "CODES", value_delimiter = ',', value_parser = value_parser!(u16).range(100..=999))]
+    expect_status: Vec<u16>,
+
+    /// Fail responses without this header, or with another value if one is given (can be repeated).
+    #[arg(long = "expect-header", value_name = "NAME[: VALUE]")]
+    expect_header: Vec<String>,
+
+    /// Fail responses whose body does not contain this text (can be repeated).
+    #[arg(long = "expect-body-contains", value_name = "TEXT")]
+    expect_body_contains: Vec<String>,
+
+    /// Fail responses whose body does not match this regular expression (can be repeated).
+    #[arg(long = "expect-body-regex", value_name = "REGEX")]
+    expect_body_regex: Vec<String>,
+
+    /// Fail responses whose JSON body does not hold VALUE at the JSON POINTER, e.g. /status="ok" or
+    /// /count=3. A VALUE that is not valid JSON is compared as a string (can be repeated).
+    #[arg(long = "expect-json", value_name = "POINTER=VALUE")]
+    expect_json: Vec<String>,
+
+    /// Keep at most BYTES of the response body for --expect-body-regex and --expect-json; a longer
+    /// body fails them. --expect-body-contains searches the body as it streams and has no limit.
+    #[arg(long = "expect-body-max-bytes", value_name = "BYTES", default_value = "1048576", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    expect_body_max_bytes: usize,
+
     /// Target requests per second (rate limit).
     #[arg(long = "requests-per-second", value_name = "RPS", default_value = "1", value_parser = value_parser!(rust_decimal::Decimal))]
     requests_per_second: rust_decimal::Decimal,
 
+    /// Run N closed-loop virtual users instead of a fixed request rate. Each user sends a request,
+    /// waits for the response, optionally sleeps for --think-time, and repeats.
+    #[arg(long = "concurrency", value_name = "USERS", conflicts_with = "requests_per_second", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    concurrency: Option<usize>,
+
+    /// Think time between a response and the next request of a virtual user (seconds, decimal allowed).
+    #[arg(long = "think-time", value_name = "SECONDS", requires = "closed_loop", value_parser = value_parser!(f64))]
+    think_time_secs: Option<f64>,
+
+    /// Ramp the request rate linearly from 0 to --requests-per-second over this many seconds.
+    #[arg(long = "ramp-up", value_name = "SECONDS", conflicts_with = "concurrency", value_parser = value_parser!(f64))]
+    ramp_up_secs: Option<f64>,
+
+    /// Ramp the request rate linearly down to 0 over the last this many seconds of the run.
+    #[arg(long = "ramp-down", value_name = "SECONDS", conflicts_with = "concurrency", value_parser = value_parser!(f64))]
+    ramp_down_secs: Option<f64>,
+
+    /// File of DURATION@TARGET stages run back to back (e.g. "1m@100rps" or "30s@20vu" per line).
+    #[arg(long = "stages", value_name = "PATH", conflicts_with_all = ["requests_per_second", "concurrency", "duration_secs", "ramp_up_secs", "ramp_down_secs"])]
+    stages: Option<PathBuf>,
+
+    /// Arrival process of open-loop requests: evenly spaced, Poisson, or uniformly jittered gaps.
+    #[arg(
+        long = "arrival",
+        value_name = "PROCESS",
+        value_enum,
+        default_value_t,
+        conflicts_with = "concurrency"
+    )]
+    arrival: CliArrival,
+
+    /// Seed for the random arrival processes, to make a run's schedule reproducible.
+    #[arg(long = "seed", value_name = "SEED")]
+    seed: Option<u64>,
+
+    /// Maximum number of open-loop requests in flight; requests scheduled beyond it are counted as
+    /// dropped.
+    #[arg(long = "max-in-flight", value_name = "REQUESTS", conflicts_with = "concurrency", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_in_flight: Option<usize>,
+
+    /// Number of scheduler workers sharing the load (default: one per runtime thread).
+    #[arg(long = "workers", value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    workers: Option<usize>,
+
+    /// Number of runtime threads driving the workers (default: one per CPU core).
+    #[arg(long = "threads", value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    threads: Option<usize>,
+
+    /// Warm-up at the start of the run (seconds, decimal allowed): requests are sent, but their
+    /// latencies are left out of the report's CDFs.
+    #[arg(long = "warmup", value_name = "SECONDS", value_parser = value_parser!(f64))]
+    warmup_secs: Option<f64>,
+
+    /// Send exactly this many requests, then stop (instead of running for --duration).
+    #[arg(short = 'n', long = "requests", value_name = "N", conflicts_with_all = ["duration_secs", "stages", "ramp_down_secs"], value_parser = value_parser!(u64).range(1..))]
+    requests: Option<u64>,
+
+    /// Search for the highest --requests-per-second meeting the SLOs: probe runs of --duration
+    /// seconds each, starting at --requests-per-second. Up to 16 probes run, so the search can take
+    /// 16 times --duration; Ctrl-C stops it with the probes so far.
+    #[arg(long = "search", requires = "slo", conflicts_with_all = ["closed_loop", "requests", "ramp_up_secs", "ramp_down_secs"])]
+    search: bool,
+
+    /// Search SLO: maximum p99 response time (seconds, decimal allowed).
+    #[arg(long = "slo-p99", value_name = "SECONDS", requires = "search", value_parser = value_parser!(f64))]
+    slo_p99_secs: Option<f64>,
+
+    /// Search SLO: maximum share of requests without a successful response, in percent (0 to 100,
+    /// e.g. 1 for 1%).
+    #[arg(long = "slo-error-rate", value_name = "PERCENT", requires = "search", value_parser = value_parser!(f64))]
+    slo_error_rate: Option<f64>,
+
+    /// Stop searching once the passing and failing rates are within this many percent.
+    #[arg(long = "search-tolerance", value_name = "PERCENT", default_value = "5", value_parser = value_parser!(f64))]
+    search_tolerance: f64,
+
+    /// Adjust the request rate (starting at --requests-per-second) to hold the --target-percentile
+    /// response time at this many seconds (decimal allowed).
+    #[arg(long = "target-latency", value_name = "SECONDS", conflicts_with_all = ["closed_loop", "requests", "search", "ramp_up_secs", "ramp_down_secs"], value_parser = value_parser!(f64))]
+    target_latency_secs: Option<f64>,
+
+    /// Response-time percentile held at --target-latency (e.g. 50 or 99).
+    #[arg(long = "target-percentile", value_name = "PERCENT", default_value = "99", requires = "target_latency_secs", value_parser = value_parser!(f64))]
+    target_percentile: f64,
+
+    /// Seconds between rate adjustments when holding --target-latency (decimal allowed).
+    #[arg(long = "adapt-interval", value_name = "SECONDS", default_value = "1", requires = "target_latency_secs", value_parser = value_parser!(f64))]
+    adapt_interval_secs: f64,
+
     /// Test duration in seconds.
     #[arg(long = "duration", value_name = "SECS", default_value = "10", value_parser = value_parser!(u64).range(1..))]
     duration_secs: u64,
 
+    /// Time to wait for in-flight requests after the test duration (seconds, decimal allowed);
+    /// requests still running then are cancelled and reported as abandoned. Without it, every
+    /// request is waited for, or at most 5 seconds once the run is interrupted or aborted.
+    #[arg(long = "drain-timeout", value_name = "SECONDS", value_parser = value_parser!(f64))]
+    drain_timeout_secs: Option<f64>,
+
+    /// Abort the run once the share of non-success outcomes over the last --abort-window goes above
+    /// this fraction (0 to 1, e.g. 0.5 for 50%; unlike --slo-error-rate, not a percent).
+    #[arg(long = "abort-on-error-rate", value_name = "RATIO", value_parser = value_parser!(f64))]
+    abort_on_error_rate: Option<f64>,
+
+    /// Seconds of outcomes --abort-on-error-rate is measured over (decimal allowed).
+    #[arg(long = "abort-window", value_name = "SECONDS", default_value = "10", requires = "abort_on_error_rate", value_parser = value_parser!(f64))]
+    abort_window_secs: f64,
+
     /// Maximum time to run the test (seconds).
     #[arg(short = 'm', long = "max-time", value_name = "SECONDS", value_parser = value_parser!(u64).range(1..))]
     max_time_secs: Option<u64>,
@@ -162,6 +404,20 @@ struct Cli {
     #[arg(short = 'o', long = "output", value_name = "PATH")]
     output: Option<PathBuf>,
 
+    /// Stream every request to this path, as one length-delimited protobuf record per request.
+    #[arg(long = "events", value_name = "PATH")]
+    events: Option<PathBuf>,
+
+    /// Significant decimal digits kept by the latency and size histograms (1 to 5). Each extra digit
+    /// makes percentiles ten times finer, for about ten times the memory.
+    #[arg(long = "histogram-precision", value_name = "DIGITS", default_value_t = histogram::DEFAULT_PRECISION, value_parser = value_parser!(u8).range(1..=5))]
+    histogram_precision: u8,
+
+    /// Seconds between snapshots of the workers' counters and of the latency percentiles over time
+    /// (decimal allowed, at least 0.01).
+    #[arg(long = "snapshot-interval", value_name = "SECONDS", default_value = "0.25", value_parser = value_parser!(f64))]
+    snapshot_interval_secs: f64,
+
     #[command(flatten)]
     protocol: CliProtocol,
 
@@ -188,6 +444,44 @@ async fn args_from_cli(cli: Cli) -> Result<Args> {
         source,
     })?;
     let protocol = resolve_protocol(&cli.protocol)?;
+    let stages = resolve_stages(&cli).await?;
+    let ramp_up = optional_seconds("--ramp-up", cli.ramp_up_secs)?;
+    let ramp_down = optional_seconds("--ramp-down", cli.ramp_down_secs)?;
+    let duration = if let Some(ref stages) = stages {
+        stages.iter().map(|s| s.duration).sum()
+    } else if let Some(requests) = cli.requests {
+        requests_duration(&cli, requests)?
+    } else {
+        Duration::from_secs(cli.duration_secs)
+    };
+    let ramps = ramp_up
+        .unwrap_or_default()
+        .saturating_add(ramp_down.unwrap_or_default());
+    if ramps > duration {
+        return Err(if cli.requests.is_some() {
+            AppError::RampUpExceedsRequests
+        } else {
+            AppError::RampsExceedDuration
+        });
+    }
+    let warmup = optional_seconds("--warmup", cli.warmup_secs)?;
+    // A closed-loop request budget has no fixed length to compare the warm-up with.
+    if !duration.is_zero() && warmup.is_some_and(|warmup| warmup >= duration) {
+        return Err(AppError::WarmupExceedsDuration);
+    }
+
+    let in_percent_range = |percent: f64| (0.0_f64..=100.0_f64).contains(&percent);
+    if !cli.slo_error_rate.is_none_or(in_percent_range)
+        || !in_percent_range(cli.search_tolerance)
+        || !in_percent_range(cli.target_percentile)
+    {
+        return Err(AppError::InvalidPercentage);
+    }
+    let search = resolve_search(&cli)?;
+    let adaptive = resolve_adaptive(&cli)?;
+    let abort = resolve_abort(&cli)?;
+    let snapshot_interval = resolve_snapshot_interval(&cli)?;
+    let assertions = resolve_assertions(&cli)?;
 
     if cli.upload_file.is_some() && cli.data.is_some() {
         return Err(AppError::MutuallyExclusiveUploadFileAndData);
@@ -208,13 +502,31 @@ async fn args_from_cli(cli: Cli) -> Result<Args> {
         key: cli.key,
         location: cli.location,
         requests_per_second: cli.requests_per_second,
-        duration: Duration::from_secs(cli.duration_secs),
+        concurrency: cli.concurrency,
+        think_time: optional_seconds("--think-time", cli.think_time_secs)?,
+        ramp_up,
+        ramp_down,
+        stages,
+        arrival: cli.arrival.into(),
+        seed: cli.seed.unwrap_or_else(rand::random),
+        max_in_flight: cli.max_in_flight,
+        workers: cli.workers,
+        threads: cli.threads,
+        warmup,
+        requests: cli.requests,
+        adaptive,
+        search,
+        duration,
+        drain_timeout: optional_seconds("--drain-timeout", cli.drain_timeout_secs)?,
+        abort,
+        assertions,
+        expect_body_max_bytes: cli.expect_body_max_bytes,
         max_time: cli.max_time_secs.map(Duration::from_secs),
         connect_timeout: cli.connect_timeout_secs.map(Duration::from_secs_f64),
-        output: cli.output.unwrap_or_else(|| {
-            let ts = chrono::Utc::now().format("%Y%m%dT%H%M%S");
-            PathBuf::from(format!("loadtest-report-{ts}.pb"))
-        }),
+        output: cli.output.unwrap_or_else(default_output_path),
+        events: cli.events,
+        histogram_precision: cli.histogram_precision,
+        snapshot_interval,
         protocol,
         payload,
         identity,
@@ -233,6 +545,69 @@ fn parse_headers(header_strs: &[String]) -> Result<http::HeaderMap> {
     Ok(header)
 }
 
+/// Response assertions from the --expect-* flags: status, headers, body text, body regexes, then
+/// JSON values.
+fn resolve_assertions(cli: &Cli) -> Result<Vec<Assertion>> {
+    let mut assertions = Vec::new();
+    if !cli.expect_status.is_empty() {
+        assertions.push(Assertion::Status(cli.expect_status.clone()));
+    }
+    for raw in &cli.expect_header {
+        let assertion = if raw.contains(':') {
+            let (name, value) = parse_header_line(raw)?;
+            Assertion::Header {
+                name,
+                value: Some(value),
+            }
+        } else {
+            let name = http::HeaderName::from_str(raw.trim()).map_err(|source| {
+                AppError::InvalidHeaderName {
+                    raw: raw.clone(),
+                    source,
+                }
+            })?;
+            Assertion::Header { name, value: None }
+        };
+        assertions.push(assertion);
+    }
+    assertions.extend(
+        cli.expect_body_contains
+            .iter()
+            .cloned()
+            .map(Assertion::BodyContains),
+    );
+    for raw in &cli.expect_body_regex {
+        let regex = regex::bytes::Regex::new(raw).map_err(|source| AppError::InvalidRegex {
+            raw: raw.clone(),
+            source,
+        })?;
+        assertions.push(Assertion::BodyRegex(regex));
+    }
+    for raw in &cli.expect_json {
+        assertions.push(parse_json_assertion(raw)?);
+    }
+    Ok(assertions)
+}
+
+/// Parses `POINTER=VALUE` (--expect-json).
+fn parse_json_assertion(raw: &str) -> Result<Assertion> {
+    let Some((pointer, value)) = raw
+        .split_once('=')
+        .filter(|&(pointer, _)| pointer.is_empty() || pointer.starts_with('/'))
+    else {
+        return Err(AppError::InvalidJsonAssertion {
+            raw: raw.to_owned(),
+        });
+    };
+    // Not JSON (e.g. an unquoted word): compare it as a string.
+    let value =
+        serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_owned()));
+    Ok(Assertion::Json {
+        pointer: pointer.to_owned(),
+        value,
+    })
+}
+
 fn resolve_protocol(protocol: &CliProtocol) -> Result<HttpProtocol> {
     if protocol.http1_1 && protocol.http2_prior_knowledge {
         return Err(AppError::MutuallyExclusiveProtocols);
@@ -331,6 +706,124 @@ async fn resolve_identity(cli: &Cli) -> Result<Option<Identity>> {
     }
 }
 
+/// Time the open-loop schedule takes to send `requests` at --requests-per-second, plus half the
+/// ramp-up (the rate averages half its peak there). Closed-loop runs are bounded by the request
+/// count alone and get a zero duration.
+fn requests_duration(cli: &Cli, requests: u64) -> Result<Duration> {
+    if cli.concurrency.is_some() {
+        return Ok(Duration::ZERO);
+    }
+    let rate = cli
+        .requests_per_second
+        .to_f64()
+        .filter(|&rate| rate > 0.0_f64)
+        .ok_or(AppError::RequestsNeedRate)?;
+    let requests = requests.to_f64().expect("request count fits in f64");
+    let ramp_up = cli.ramp_up_secs.unwrap_or(0.0_f64);
+    Duration::try_from_secs_f64(ramp_up.mul_add(0.5_f64, requests / rate))
+        .ok()
+        .ok_or(AppError::RequestsNeedRate)
+}
+
+/// `secs` as a duration, or an error naming `flag` if it is negative, not a number, or too large.
+fn seconds(flag: &'static str, secs: f64) -> Result<Duration> {
+    Duration::try_from_secs_f64(secs).map_err(|source| AppError::InvalidSeconds {
+        flag,
+        value: secs,
+        source,
+    })
+}
+
+/// [`seconds`] of a flag that may be absent.
+fn optional_seconds(flag: &'static str, secs: Option<f64>) -> Result<Option<Duration>> {
+    secs.map(|secs| seconds(flag, secs)).transpose()
+}
+
+/// Rate search settings, when --search is given.
+fn resolve_search(cli: &Cli) -> Result<Option<Search>> {
+    if !cli.search {
+        return Ok(None);
+    }
+    if cli.requests_per_second <= rust_decimal::Decimal::ZERO {
+        return Err(AppError::SearchNeedsRate);
+    }
+    Ok(Some(Search {
+        slo_p99: optional_seconds("--slo-p99", cli.slo_p99_secs)?,
+        slo_error_rate: cli.slo_error_rate.map(|percent| percent / 100.0_f64),
+        tolerance: cli.search_tolerance / 100.0_f64,
+    }))
+}
+
+/// Adaptive rate control settings, when --target-latency is given.
+fn resolve_adaptive(cli: &Cli) -> Result<Option<Adaptive>> {
+    let Some(target_latency) = cli.target_latency_secs else {
+        return Ok(None);
+    };
+    if cli.requests_per_second <= rust_decimal::Decimal::ZERO {
+        return Err(AppError::AdaptiveNeedsRate);
+    }
+    let interval = seconds("--adapt-interval", cli.adapt_interval_secs)?;
+    if interval.is_zero() {
+        return Err(AppError::ZeroAdaptInterval);
+    }
+    Ok(Some(Adaptive {
+        target_latency: seconds("--target-latency", target_latency)?,
+        percentile: cli.target_percentile / 100.0_f64,
+        interval,
+    }))
+}
+
+/// Abort settings, when --abort-on-error-rate is given.
+fn resolve_abort(cli: &Cli) -> Result<Option<AbortPolicy>> {
+    let Some(error_rate) = cli.abort_on_error_rate else {
+        return Ok(None);
+    };
+    if !(0.0_f64..=1.0_f64).contains(&error_rate) {
+        return Err(AppError::InvalidAbortErrorRate);
+    }
+    let window = seconds("--abort-window", cli.abort_window_secs)?;
+    if window.is_zero() {
+        return Err(AppError::ZeroAbortWindow);
+    }
+    Ok(Some(AbortPolicy { error_rate, window }))
+}
+
+/// loadtest-report-YYYYMMDDTHHMMSS.pb, stamped with the current time.
+fn default_output_path() -> PathBuf {
+    let ts = chrono::Utc::now().format("%Y%m%dT%H%M%S");
+    PathBuf::from(format!("loadtest-report-{ts}.pb"))
+}
+
+fn resolve_snapshot_interval(cli: &Cli) -> Result<Duration> {
+    let interval = seconds("--snapshot-interval", cli.snapshot_interval_secs)?;
+    if interval < MIN_SNAPSHOT_INTERVAL {
+        return Err(AppError::SnapshotIntervalTooShort);
+    }
+    Ok(interval)
+}
+
+async fn resolve_stages(cli: &Cli) -> Result<Option<Vec<Stage>>> {
+    let Some(ref path) = cli.stages else {
+        return Ok(None);
+    };
+    let text = fs::read_to_string(path)
+        .await
+        .map_err(|source| AppError::FailedToReadFile {
+            path: path.clone(),
+            source,
+        })?;
+    let stages = parse_stages(&text)?;
+    // Virtual-user stages run closed-loop: there is no arrival schedule to shape, and every user
+    // already waits for its response.
+    let users = stages
+        .iter()
+        .any(|stage| matches!(stage.target, StageTarget::Users(_)));
+    if users && (!matches!(cli.arrival, CliArrival::Constant) || cli.max_in_flight.is_some()) {
+        return Err(AppError::OpenLoopFlagWithUserStages);
+    }
+    Ok(Some(stages))
+}
+
 async fn resolve_root_certificates(cli: &Cli) -> Result<Option<Vec<Certificate>>> {
     if cli.dry_run {
         return Ok(None);
@@ -390,7 +883,7 @@ mod tests {
         args_from_cli(cli).await
     }
 
-    /// Table-driven tests: parse_from(argv) succeeds; use `|args| { ... }` so the binding is in scope.
+    /// Table-driven tests: `parse_from(argv)` succeeds; use `|args| { ... }` so the binding is in scope.
     macro_rules! parse_from_ok_tests {
         ($($name:ident: $argv:expr => |$args:ident| $body:block),* $(,)?) => {
             $(
@@ -403,7 +896,7 @@ mod tests {
         };
     }
 
-    /// Table-driven tests: parse_from(argv) fails and the error matches the given pattern.
+    /// Table-driven tests: `parse_from(argv)` fails and the error matches the given pattern.
     macro_rules! parse_from_err_tests {
         ($($name:ident: $argv:expr => $err_pat:pat),* $(,)?) => {
             $(
@@ -425,6 +918,8 @@ mod tests {
             assert!(!args.location);
             assert_eq!(args.requests_per_second, rust_decimal::Decimal::from(1));
             assert_eq!(args.duration, Duration::from_secs(10));
+            assert!(args.concurrency.is_none());
+            assert!(args.think_time.is_none());
             assert!(args.max_time.is_none());
             assert!(args.connect_timeout.is_none());
             assert!(args.payload.is_none());
@@ -432,7 +927,7 @@ mod tests {
             assert!(args.root_certificates.is_none());
             assert!(!args.dry_run);
             assert!(args.output.as_os_str().to_str().unwrap().starts_with("loadtest-report-"));
-            assert!(args.output.as_os_str().to_str().unwrap().ends_with(".pb"));
+            assert_eq!(args.output.extension().unwrap(), "pb");
         },
         parse_http2_prior_knowledge: &["https://example.com/", "--http2-prior-knowledge"] => |args| {
             assert!(matches!(args.protocol, HttpProtocol::Http2));
@@ -448,6 +943,187 @@ mod tests {
             assert_eq!(args.duration, Duration::from_secs(5));
             assert_eq!(args.requests_per_second, rust_decimal::Decimal::from(10));
         },
+        parse_concurrency_and_think_time: &[
+            "https://example.com/",
+            "--http1.1",
+            "--concurrency",
+            "8",
+            "--think-time",
+            "0.5",
+        ] => |args| {
+            assert_eq!(args.concurrency, Some(8));
+            assert_eq!(args.think_time, Some(Duration::from_millis(500)));
+        },
+        parse_ramp_up_and_ramp_down: &[
+            "https://example.com/",
+            "--http1.1",
+            "--duration",
+            "60",
+            "--ramp-up",
+            "30",
+            "--ramp-down",
+            "10.5",
+        ] => |args| {
+            assert_eq!(args.ramp_up, Some(Duration::from_secs(30)));
+            assert_eq!(args.ramp_down, Some(Duration::from_millis(10_500)));
+        },
+        parse_poisson_arrival_with_seed: &[
+            "https://example.com/",
+            "--http1.1",
+            "--arrival",
+            "poisson",
+            "--seed",
+            "42",
+        ] => |args| {
+            assert_eq!(args.arrival, ArrivalProcess::Poisson);
+            assert_eq!(args.seed, 42);
+        },
+        parse_workers_and_threads: &[
+            "https://example.com/",
+            "--http1.1",
+            "--workers",
+            "3",
+            "--threads",
+            "2",
+        ] => |args| {
+            assert_eq!(args.workers, Some(3));
+            assert_eq!(args.threads, Some(2));
+        },
+        parse_warmup: &["https://example.com/", "--http1.1", "--warmup", "2.5"] => |args| {
+            assert_eq!(args.warmup, Some(Duration::from_millis(2500)));
+        },
+        parse_requests_open_loop: &[
+            "https://example.com/",
+            "--http1.1",
+            "-n",
+            "100",
+            "--requests-per-second",
+            "20",
+        ] => |args| {
+            assert_eq!(args.requests, Some(100));
+            assert_eq!(args.duration, Duration::from_secs(5));
+        },
+        parse_requests_with_ramp_up: &[
+            "https://example.com/",
+            "--http1.1",
+            "--requests",
+            "100",
+            "--requests-per-second",
+            "20",
+            "--ramp-up",
+            "4",
+        ] => |args| {
+            assert_eq!(args.duration, Duration::from_secs(7));
+        },
+        parse_requests_closed_loop: &[
+            "https://example.com/",
+            "--http1.1",
+            "-n",
+            "1000",
+            "--concurrency",
+            "10",
+            "--warmup",
+            "1",
+        ] => |args| {
+            assert_eq!(args.requests, Some(1000));
+            assert_eq!(args.duration, Duration::ZERO);
+        },
+        parse_search: &[
+            "https://example.com/",
+            "--http1.1",
+            "--search",
+            "--slo-p99",
+            "0.25",
+            "--slo-error-rate",
+            "1",
+            "--requests-per-second",
+            "100",
+        ] => |args| {
+            let search = args.search.unwrap();
+            assert_eq!(search.slo_p99, Some(Duration::from_millis(250)));
+            assert_eq!(search.slo_error_rate, Some(0.01));
+            assert!((search.tolerance - 0.05).abs() < 1e-12);
+            assert_eq!(args.requests_per_second, rust_decimal::Decimal::from(100));
+        },
+        parse_target_latency: &[
+            "https://example.com/",
+            "--http1.1",
+            "--target-latency",
+            "0.2",
+            "--target-percentile",
+            "50",
+            "--adapt-interval",
+            "0.5",
+        ] => |args| {
+            let adaptive = args.adaptive.unwrap();
+            assert_eq!(adaptive.target_latency, Duration::from_millis(200));
+            assert!((adaptive.percentile - 0.5).abs() < 1e-12);
+            assert_eq!(adaptive.interval, Duration::from_millis(500));
+        },
+        parse_target_latency_defaults: &["https://example.com/", "--http1.1", "--target-latency", "1"] => |args| {
+            let adaptive = args.adaptive.unwrap();
+            assert!((adaptive.percentile - 0.99).abs() < 1e-12);
+            assert_eq!(adaptive.interval, Duration::from_secs(1));
+        },
+        parse_abort_on_error_rate: &[
+            "https://example.com/",
+            "--http1.1",
+            "--abort-on-error-rate",
+            "0.5",
+            "--abort-window",
+            "2.5",
+        ] => |args| {
+            let abort = args.abort.unwrap();
+            assert!((abort.error_rate - 0.5).abs() < 1e-12);
+            assert_eq!(abort.window, Duration::from_millis(2500));
+        },
+        parse_abort_window_default: &["https://example.com/", "--http1.1", "--abort-on-error-rate", "0.1"] => |args| {
+            assert_eq!(args.abort.unwrap().window, Duration::from_secs(10));
+        },
+        parse_assertions: &[
+            "https://example.com/",
+            "--http1.1",
+            "--expect-json",
+            "/status=\"ok\"",
+            "--expect-status",
+            "200,204",
+            "--expect-header",
+            "Content-Type: application/json",
+            "--expect-header",
+            "x-request-id",
+            "--expect-body-contains",
+            "welcome",
+            "--expect-body-regex",
+            "id: [0-9]+",
+            "--expect-json",
+            "/count=3",
+            "--expect-json",
+            "/state=ready",
+        ] => |args| {
+            let described: Vec<String> = args.assertions.iter().map(ToString::to_string).collect();
+            assert_eq!(
+                described,
+                [
+                    "status in 200,204",
+                    "header content-type: application/json",
+                    "header x-request-id present",
+                    "body contains \"welcome\"",
+                    "body matches /id: [0-9]+/",
+                    "JSON /status == \"ok\"",
+                    "JSON /count == 3",
+                    "JSON /state == \"ready\"",
+                ]
+            );
+        },
+        parse_expect_body_max_bytes: &["https://example.com/", "--http1.1", "--expect-body-max-bytes", "4096"] => |args| {
+            assert_eq!(args.expect_body_max_bytes, 4096);
+        },
+        parse_drain_timeout: &["https://example.com/", "--http1.1", "--drain-timeout", "0.5"] => |args| {
+            assert_eq!(args.drain_timeout, Some(Duration::from_millis(500)));
+        },
+        parse_max_in_flight: &["https://example.com/", "--http1.1", "--max-in-flight", "64"] => |args| {
+            assert_eq!(args.max_in_flight, Some(64));
+        },
         parse_headers: &[
             "https://example.com/",
             "--http1.1",
@@ -477,6 +1153,30 @@ mod tests {
         ] => |args| {
             assert_eq!(args.output.as_path(), std::path::Path::new("/tmp/report.pb"));
         },
+        parse_events_path: &[
+            "https://example.com/",
+            "--http1.1",
+            "--events",
+            "/tmp/events.pb",
+        ] => |args| {
+            assert_eq!(args.events.as_deref(), Some(std::path::Path::new("/tmp/events.pb")));
+        },
+        parse_histogram_precision: &[
+            "https://example.com/",
+            "--http1.1",
+            "--histogram-precision",
+            "2",
+        ] => |args| {
+            assert_eq!(args.histogram_precision, 2);
+        },
+        parse_snapshot_interval: &[
+            "https://example.com/",
+            "--http1.1",
+            "--snapshot-interval",
+            "1.5",
+        ] => |args| {
+            assert_eq!(args.snapshot_interval, Duration::from_millis(1500));
+        },
         parse_dry_run: &["https://example.com/", "--http1.1", "--dry-run"] => |args| {
             assert!(args.dry_run);
         },
@@ -487,8 +1187,8 @@ mod tests {
             "hello world",
         ] => |args| {
             let payload = args.payload.as_ref().unwrap();
-            match payload {
-                Payload::Data(b) => assert_eq!(b.as_ref(), b"hello world"),
+            match *payload {
+                Payload::Data(ref b) => assert_eq!(b.as_ref(), b"hello world"),
                 Payload::File(_) => panic!("expected Data payload"),
             }
             assert_eq!(args.request, http::Method::POST);
@@ -580,6 +1280,271 @@ mod tests {
             "--key",
             "/key.pem",
         ] => AppError::CertMustBeProvidedWithKey,
+        parse_concurrency_with_rps_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--concurrency",
+            "4",
+            "--requests-per-second",
+            "10",
+        ] => AppError::Clap(_),
+        parse_think_time_without_concurrency_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--think-time",
+            "1",
+        ] => AppError::Clap(_),
+        parse_ramps_longer_than_duration_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--duration",
+            "10",
+            "--ramp-up",
+            "8",
+            "--ramp-down",
+            "5",
+        ] => AppError::RampsExceedDuration,
+        parse_ramp_with_concurrency_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--concurrency",
+            "4",
+            "--ramp-up",
+            "5",
+        ] => AppError::Clap(_),
+        parse_search_without_slo_fails: &["https://example.com/", "--http1.1", "--search"] => AppError::Clap(_),
+        parse_slo_without_search_fails: &["https://example.com/", "--http1.1", "--slo-p99", "1"] => AppError::Clap(_),
+        parse_search_with_concurrency_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--search",
+            "--slo-p99",
+            "1",
+            "--concurrency",
+            "4",
+        ] => AppError::Clap(_),
+        parse_slo_error_rate_out_of_range_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--search",
+            "--slo-error-rate",
+            "150",
+        ] => AppError::InvalidPercentage,
+        parse_target_latency_with_search_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--target-latency",
+            "1",
+            "--search",
+            "--slo-p99",
+            "1",
+        ] => AppError::Clap(_),
+        parse_target_percentile_without_target_latency_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--target-percentile",
+            "50",
+        ] => AppError::Clap(_),
+        parse_target_latency_at_zero_rate_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--target-latency",
+            "1",
+            "--requests-per-second",
+            "0",
+        ] => AppError::AdaptiveNeedsRate,
+        parse_zero_adapt_interval_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--target-latency",
+            "1",
+            "--adapt-interval",
+            "0",
+        ] => AppError::ZeroAdaptInterval,
+        parse_abort_window_without_error_rate_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--abort-window",
+            "5",
+        ] => AppError::Clap(_),
+        parse_abort_error_rate_above_one_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--abort-on-error-rate",
+            "50",
+        ] => AppError::InvalidAbortErrorRate,
+        parse_zero_abort_window_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--abort-on-error-rate",
+            "0.5",
+            "--abort-window",
+            "0",
+        ] => AppError::ZeroAbortWindow,
+        parse_negative_think_time_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--concurrency",
+            "2",
+            "--think-time=-1",
+        ] => AppError::InvalidSeconds { flag: "--think-time", .. },
+        parse_negative_ramp_up_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--ramp-up=-2",
+        ] => AppError::InvalidSeconds { flag: "--ramp-up", .. },
+        parse_negative_ramp_down_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--requests-per-second",
+            "10",
+            "--ramp-down=-2",
+        ] => AppError::InvalidSeconds { flag: "--ramp-down", .. },
+        parse_negative_warmup_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--warmup=-1",
+        ] => AppError::InvalidSeconds { flag: "--warmup", .. },
+        parse_negative_drain_timeout_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--drain-timeout=-0.5",
+        ] => AppError::InvalidSeconds { flag: "--drain-timeout", .. },
+        parse_negative_slo_p99_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--search",
+            "--slo-p99=-0.1",
+        ] => AppError::InvalidSeconds { flag: "--slo-p99", .. },
+        parse_negative_target_latency_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--target-latency=-0.2",
+        ] => AppError::InvalidSeconds { flag: "--target-latency", .. },
+        parse_negative_adapt_interval_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--target-latency",
+            "0.2",
+            "--adapt-interval=-1",
+        ] => AppError::InvalidSeconds { flag: "--adapt-interval", .. },
+        parse_negative_abort_window_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--abort-on-error-rate",
+            "0.5",
+            "--abort-window=-3",
+        ] => AppError::InvalidSeconds { flag: "--abort-window", .. },
+        parse_zero_snapshot_interval_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--snapshot-interval",
+            "0",
+        ] => AppError::SnapshotIntervalTooShort,
+        parse_tiny_snapshot_interval_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--snapshot-interval",
+            "1e-9",
+        ] => AppError::SnapshotIntervalTooShort,
+        parse_negative_snapshot_interval_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--snapshot-interval=-1",
+        ] => AppError::InvalidSeconds { flag: "--snapshot-interval", .. },
+        parse_invalid_expect_status_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--expect-status",
+            "200,abc",
+        ] => AppError::Clap(_),
+        parse_invalid_expect_header_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--expect-header",
+            "bad header",
+        ] => AppError::InvalidHeaderName { .. },
+        parse_invalid_expect_body_regex_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--expect-body-regex",
+            "(unclosed",
+        ] => AppError::InvalidRegex { .. },
+        parse_expect_json_without_pointer_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--expect-json",
+            "status=ok",
+        ] => AppError::InvalidJsonAssertion { .. },
+        parse_requests_with_duration_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "-n",
+            "100",
+            "--duration",
+            "5",
+        ] => AppError::Clap(_),
+        parse_requests_at_zero_rate_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "-n",
+            "100",
+            "--requests-per-second",
+            "0",
+        ] => AppError::RequestsNeedRate,
+        parse_requests_shorter_than_ramp_up_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "-n",
+            "10",
+            "--requests-per-second",
+            "10",
+            "--ramp-up",
+            "5",
+        ] => AppError::RampUpExceedsRequests,
+        parse_warmup_longer_than_duration_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--duration",
+            "5",
+            "--warmup",
+            "5",
+        ] => AppError::WarmupExceedsDuration,
+        parse_zero_workers_fails: &["https://example.com/", "--http1.1", "--workers", "0"] => AppError::Clap(_),
+        parse_zero_max_in_flight_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--max-in-flight",
+            "0",
+        ] => AppError::Clap(_),
+        parse_unknown_arrival_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--arrival",
+            "bursty",
+        ] => AppError::Clap(_),
+        parse_arrival_with_concurrency_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--concurrency",
+            "4",
+            "--arrival",
+            "uniform",
+        ] => AppError::Clap(_),
+        parse_stages_with_duration_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--stages",
+            "stages.txt",
+            "--duration",
+            "10",
+        ] => AppError::Clap(_),
+        parse_missing_stages_file_fails: &[
+            "https://example.com/",
+            "--http1.1",
+            "--stages",
+            "/nonexistent/loadtest-stages.txt",
+        ] => AppError::FailedToReadFile { .. },
     }
 
     /// Fails to compile if a new field is added to `Args` without updating this test (and the coverage matrix).
@@ -598,35 +1563,76 @@ mod tests {
             key,
             location,
             requests_per_second,
+            concurrency,
+            think_time,
+            ramp_up,
+            ramp_down,
+            stages,
+            arrival,
+            seed: _seed,
+            max_in_flight,
+            workers,
+            threads,
+            warmup,
+            requests,
+            search,
+            adaptive,
             duration,
+            drain_timeout,
+            abort,
+            assertions,
+            expect_body_max_bytes,
             max_time,
             connect_timeout,
             output,
+            events,
+            histogram_precision,
+            snapshot_interval,
             protocol,
             payload,
             identity,
             root_certificates,
             dry_run,
             upload_file_path,
-        } = &args;
+        } = args;
         assert!(!url.as_str().is_empty());
         assert!(header.is_empty());
-        assert!(!*insecure);
+        assert!(!insecure);
         assert_eq!(request.as_str(), "GET");
         assert!(cacert.is_none());
         assert!(cert.is_none());
         assert!(key.is_none());
-        assert!(!*location);
-        assert_eq!(requests_per_second, &rust_decimal::Decimal::from(1));
-        assert_eq!(duration, &Duration::from_secs(10));
+        assert!(!location);
+        assert_eq!(requests_per_second, rust_decimal::Decimal::from(1));
+        assert!(concurrency.is_none());
+        assert!(think_time.is_none());
+        assert!(ramp_up.is_none());
+        assert!(ramp_down.is_none());
+        assert!(stages.is_none());
+        assert_eq!(arrival, ArrivalProcess::Constant);
+        assert!(max_in_flight.is_none());
+        assert!(workers.is_none());
+        assert!(threads.is_none());
+        assert!(warmup.is_none());
+        assert!(requests.is_none());
+        assert!(search.is_none());
+        assert!(adaptive.is_none());
+        assert_eq!(duration, Duration::from_secs(10));
+        assert!(drain_timeout.is_none());
+        assert!(abort.is_none());
+        assert!(assertions.is_empty());
+        assert_eq!(expect_body_max_bytes, 1_048_576);
         assert!(max_time.is_none());
         assert!(connect_timeout.is_none());
         assert!(!output.as_os_str().is_empty());
+        assert!(events.is_none());
+        assert_eq!(histogram_precision, crate::histogram::DEFAULT_PRECISION);
+        assert_eq!(snapshot_interval, Duration::from_millis(250));
         assert!(matches!(protocol, HttpProtocol::Http1_1));
         assert!(payload.is_none());
         assert!(identity.is_none());
         assert!(root_certificates.is_none());
-        assert!(!*dry_run);
+        assert!(!dry_run);
         assert!(upload_file_path.is_none());
     }
 
@@ -646,11 +1652,97 @@ mod tests {
         .await
         .unwrap();
         let payload = args.payload.as_ref().unwrap();
-        match payload {
-            Payload::File(b) => assert_eq!(b.as_ref(), content),
+        match *payload {
+            Payload::File(ref b) => assert_eq!(b.as_ref(), content),
             Payload::Data(_) => panic!("expected File payload"),
         }
         assert_eq!(args.request, http::Method::PUT);
         let _ = tokio::fs::remove_file(&path).await;
     }
+
+    #[tokio::test]
+    async fn parse_stages_file_sets_duration() {
+        let path = std::env::temp_dir().join("loadtest-stages-file-test.txt");
+        tokio::fs::write(&path, "1m@100rps\n5m@500rps\n1m@0\n")
+            .await
+            .unwrap();
+        let path_str = path.to_string_lossy();
+        let args = parse_from(argv(&[
+            "https://example.com/",
+            "--http1.1",
+            "--stages",
+            path_str.as_ref(),
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(args.stages.as_ref().map(Vec::len), Some(3));
+        assert_eq!(args.duration, Duration::from_mins(7));
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+
+    #[tokio::test]
+    async fn parse_user_stages_with_think_time() {
+        let path = std::env::temp_dir().join("loadtest-user-stages-test.txt");
+        tokio::fs::write(&path, "10s@5vu, 20s@10vu").await.unwrap();
+        let path_str = path.to_string_lossy();
+        let args = parse_from(argv(&[
+            "https://example.com/",
+            "--http1.1",
+            "--stages",
+            path_str.as_ref(),
+            "--think-time",
+            "1",
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(args.think_time, Some(Duration::from_secs(1)));
+        assert_eq!(args.duration, Duration::from_secs(30));
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+
+    #[tokio::test]
+    async fn parse_user_stages_with_open_loop_flags_fails() {
+        let path = std::env::temp_dir().join("loadtest-user-stages-open-loop-test.txt");
+        tokio::fs::write(&path, "10s@5vu").await.unwrap();
+        let path_str = path.to_string_lossy();
+        for flag in [["--arrival", "poisson"], ["--max-in-flight", "8"]] {
+            let err = parse_from(argv(&[
+                "https://example.com/",
+                "--http1.1",
+                "--stages",
+                path_str.as_ref(),
+                flag[0],
+                flag[1],
+            ]))
+            .await
+            .unwrap_err();
+            assert!(
+                matches!(err, AppError::OpenLoopFlagWithUserStages),
+                "{flag:?}: {err:?}"
+            );
+        }
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+
+    #[tokio::test]
+    async fn parse_rate_stages_with_open_loop_flags() {
+        let path = std::env::temp_dir().join("loadtest-rate-stages-open-loop-test.txt");
+        tokio::fs::write(&path, "10s@50rps").await.unwrap();
+        let path_str = path.to_string_lossy();
+        let args = parse_from(argv(&[
+            "https://example.com/",
+            "--http1.1",
+            "--stages",
+            path_str.as_ref(),
+            "--arrival",
+            "poisson",
+            "--max-in-flight",
+            "8",
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(args.arrival, ArrivalProcess::Poisson);
+        assert_eq!(args.max_in_flight, Some(8));
+        let _ = tokio::fs::remove_file(&path).await;
+    }
 }
@@ -0,0 +1,278 @@
+//! Central open-loop dispatcher.
+//!
+//! A single task walks the arrival process over the rate profile and hands each request, as a
+//! "ticket" carrying its intended send time, to the workers in turn. Requests accrue like tokens in
+//! a bucket filled at the profile's rate: the dispatcher issues one whenever a whole request is
+//! due, so a run sends exactly `rate × duration` requests at any fractional rate, however many
+//! workers share them. With a request budget (`-n`) it issues exactly that many instead, however
+//! long the arrivals take.
+//!
+//! Each worker queues at most [`TICKET_CAPACITY`] tickets. A ticket that finds every queue full is
+//! not sent, and counted: the workers are too far behind to keep to the schedule.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::{
+    select,
+    sync::mpsc::{Sender, error::TrySendError},
+    time::Instant,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{arrival::Arrivals, profile::RateProfile};
+
+/// Slack on the request count so that float rounding does not add or lose a request at the end
+/// (e.g. 0.29 rps over 100 s must issue 29 requests, not 28 or 30).
+const REQUEST_EPSILON: f64 = 1e-6;
+
+/// Tickets a worker can have queued before the dispatcher passes it over.
+pub(crate) const TICKET_CAPACITY: usize = 1024;
+
+pub(crate) struct Dispatcher {
+    profile: Arc<RateProfile>,
+    arrivals: Arrivals,
+    /// Requests the profile calls for over the whole run.
+    total: f64,
+    /// Requests left to issue under a request budget, which replaces `total`. Random arrivals may
+    /// run past the profile's end; its final rate then holds until the budget is spent.
+    remaining: Option<u64>,
+}
+
+impl Dispatcher {
+    pub(crate) fn new(profile: Arc<RateProfile>, arrivals: Arrivals, duration: Duration) -> Self {
+        let total = profile.requests_until(duration);
+        Self {
+            profile,
+            arrivals,
+            total,
+            remaining: None,
+        }
+    }
+
+    /// Dispatcher issuing exactly `requests` requests, following the profile's rate.
+    pub(crate) fn with_budget(
+        profile: Arc<RateProfile>,
+        arrivals: Arrivals,
+        requests: u64,
+    ) -> Self {
+        Self {
+            profile,
+            arrivals,
+            total: f64::INFINITY,
+            remaining: Some(requests),
+        }
+    }
+
+    /// Elapsed time at which the next request is due, or `None` once the run's requests are issued.
+    pub(crate) fn next_send(&mut self) -> Option<Duration> {
+        let due = self.arrivals.next_due();
+        let at = match self.remaining {
+            Some(remaining) => {
+                self.remaining = Some(remaining.checked_sub(1)?);
+                self.profile.time_for_requests_held(due)?
+            }
+            None if due >= self.total - REQUEST_EPSILON => return None,
+            None => self.profile.time_for_requests(due)?,
+        };
+        self.arrivals.advance();
+        Some(at)
+    }
+
+    /// Sends each request's intended send time to `workers`, round-robin, as it falls due, counting
+    /// the tickets no worker had room for in `dropped`. Returns once every request has been issued,
+    /// `cancel` fires, or no worker is left to take tickets.
+    pub(crate) async fn run(
+        mut self,
+        start: Instant,
+        workers: Vec<Sender<Instant>>,
+        dropped: Arc<AtomicU64>,
+        cancel: CancellationToken,
+    ) {
+        let mut next_worker: usize = 0;
+        while let Some(at) = self.next_send() {
+            let scheduled = start.checked_add(at).expect("send time overflow");
+            select! {
+                () = cancel.cancelled() => return,
+                () = tokio::time::sleep_until(scheduled) => {}
+            }
+            if !hand_out(&workers, &mut next_worker, scheduled, &dropped) {
+                return;
+            }
+        }
+    }
+}
+
+/// Hands `ticket` to the next worker in turn from `next_worker`, skipping workers that have
+/// stopped or whose queue is full. A ticket no worker has room for is counted in `dropped`.
+/// Returns `false` if no worker is left.
+pub(crate) fn hand_out(
+    workers: &[Sender<Instant>],
+    next_worker: &mut usize,
+    ticket: Instant,
+    dropped: &AtomicU64,
+) -> bool {
+    let mut ticket = ticket;
+    let mut full = false;
+    for _ in 0..workers.len() {
+        let Some(worker) = workers.get(*next_worker) else {
+            return false;
+        };
+        *next_worker = next_worker
+            .checked_add(1)
+            .expect("worker index overflow")
+            .checked_rem(workers.len())
+            .expect("no workers");
+        match worker.try_send(ticket) {
+            Ok(()) => return true,
+            Err(TrySendError::Full(returned)) => {
+                full = true;
+                ticket = returned;
+            }
+            Err(TrySendError::Closed(returned)) => ticket = returned,
+        }
+    }
+    if full {
+        dropped.fetch_add(1, Ordering::Relaxed);
+    }
+    full
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ArrivalProcess;
+
+    fn constant(rps: f64, secs: u64) -> Dispatcher {
+        let duration = Duration::from_secs(secs);
+        let profile = RateProfile::ramped(rps, duration, Duration::ZERO, Duration::ZERO);
+        Dispatcher::new(
+            Arc::new(profile),
+            Arrivals::new(ArrivalProcess::Constant, 0),
+            duration,
+        )
+    }
+
+    fn schedule(mut dispatcher: Dispatcher) -> Vec<Duration> {
+        std::iter::from_fn(|| dispatcher.next_send()).collect()
+    }
+
+    #[test]
+    fn issues_exactly_rate_times_duration() {
+        assert_eq!(schedule(constant(5.0, 10)).len(), 50);
+        assert_eq!(schedule(constant(0.29, 100)).len(), 29);
+        assert_eq!(schedule(constant(1000.0, 3)).len(), 3000);
+    }
+
+    #[test]
+    fn fractional_rate_below_one_still_sends() {
+        assert_eq!(
+            schedule(constant(0.5, 10)),
+            [0, 2, 4, 6, 8].map(Duration::from_secs).to_vec()
+        );
+    }
+
+    #[test]
+    fn budget_issues_exactly_the_requested_count() {
+        for process in [
+            ArrivalProcess::Constant,
+            ArrivalProcess::Poisson,
+            ArrivalProcess::Uniform,
+        ] {
+            let profile =
+                RateProfile::ramped(20.0, Duration::from_secs(5), Duration::ZERO, Duration::ZERO);
+            let times = schedule(Dispatcher::with_budget(
+                Arc::new(profile),
+                Arrivals::new(process, 3),
+                100,
+            ));
+            assert_eq!(times.len(), 100, "{process:?}");
+        }
+    }
+
+    #[test]
+    fn zero_rate_sends_nothing() {
+        assert!(schedule(constant(0.0, 10)).is_empty());
+    }
+
+    #[test]
+    fn random_arrivals_stay_within_the_run() {
+        let duration = Duration::from_secs(10);
+        let profile = RateProfile::ramped(50.0, duration, Duration::from_secs(5), Duration::ZERO);
+        let times = schedule(Dispatcher::new(
+            Arc::new(profile),
+            Arrivals::new(ArrivalProcess::Poisson, 1),
+            duration,
+        ));
+        assert!(times.windows(2).all(|w| w[0] <= w[1]));
+        assert!(times.iter().all(|&t| t < duration));
+        // 375 requests expected; a Poisson count stays well within ±25%.
+        assert!((280..470).contains(&times.len()), "{}", times.len());
+    }
+
+    #[tokio::test]
+    async fn run_hands_tickets_round_robin() {
+        let (tx_a, mut rx_a) = tokio::sync::mpsc::channel(TICKET_CAPACITY);
+        let (tx_b, mut rx_b) = tokio::sync::mpsc::channel(TICKET_CAPACITY);
+        let start = Instant::now();
+        let dropped = Arc::new(AtomicU64::new(0));
+        constant(1000.0, 1)
+            .run(
+                start.checked_sub(Duration::from_secs(1)).unwrap(),
+                vec![tx_a, tx_b],
+                Arc::clone(&dropped),
+                CancellationToken::new(),
+            )
+            .await;
+        let (mut a, mut b) = (0, 0);
+        while rx_a.try_recv().is_ok() {
+            a += 1;
+        }
+        while rx_b.try_recv().is_ok() {
+            b += 1;
+        }
+        assert_eq!((a, b), (500, 500));
+        assert_eq!(dropped.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn hand_out_passes_over_full_queues_and_counts_what_no_one_takes() {
+        let (full, full_rx) = tokio::sync::mpsc::channel(1);
+        let (free, mut free_rx) = tokio::sync::mpsc::channel(1);
+        let (stopped, _) = tokio::sync::mpsc::channel(1);
+        full.try_send(Instant::now()).unwrap();
+        let workers = [full, free, stopped];
+        let dropped = AtomicU64::new(0);
+        let mut next_worker = 0;
+        assert!(hand_out(
+            &workers,
+            &mut next_worker,
+            Instant::now(),
+            &dropped
+        ));
+        free_rx.try_recv().unwrap();
+        assert_eq!(dropped.load(Ordering::Relaxed), 0);
+        // The free queue is now taken again: nobody has room, the ticket is dropped.
+        workers[1].try_send(Instant::now()).unwrap();
+        assert!(hand_out(
+            &workers,
+            &mut next_worker,
+            Instant::now(),
+            &dropped
+        ));
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+        // Once every worker has stopped, the dispatcher stops too.
+        drop((full_rx, free_rx));
+        assert!(!hand_out(
+            &workers,
+            &mut next_worker,
+            Instant::now(),
+            &dropped
+        ));
+    }
+}
@@ -2,7 +2,8 @@
 
 use std::fmt::Write as _;
 
-use crate::cli::{Args, Payload};
+use crate::cli::{Args, ArrivalProcess, Payload};
+use crate::stages::StageTarget;
 
 /// Returns the same content as the dry-run output, as plain text (no ANSI styling).
 /// Callers can print this to stdout or assert on it in tests.
@@ -27,6 +28,12 @@ fn format_args_request(args: &Args) -> String {
     }
     writeln!(out, "  Insecure: {}", args.insecure).expect("write to String");
     writeln!(out, "  Method: {}", args.request).expect("write to String");
+    if !args.assertions.is_empty() {
+        out.push_str("  Assertions:\n");
+        for assertion in &args.assertions {
+            writeln!(out, "    {assertion}").expect("write to String");
+        }
+    }
     out
 }
 
@@ -47,18 +54,73 @@ fn format_args_tls(args: &Args) -> String {
 fn format_args_timing(args: &Args) -> String {
     let mut out = String::new();
     writeln!(out, "  Follow redirects: {}", args.location).expect("write to String");
-    writeln!(
-        out,
-        "  Throughput: {} requests/second",
-        args.requests_per_second
-    )
-    .expect("write to String");
-    writeln!(
-        out,
-        "  Load test duration: {} seconds",
-        args.duration.as_secs()
-    )
-    .expect("write to String");
+    out.push_str(&format_args_load(args));
+    if args.concurrency.is_none() && args.arrival != ArrivalProcess::Constant {
+        writeln!(
+            out,
+            "  Arrival: {} (seed {})",
+            args.arrival.as_ref(),
+            args.seed
+        )
+        .expect("write to String");
+    }
+    if let Some(workers) = args.workers {
+        writeln!(out, "  Workers: {workers}").expect("write to String");
+    }
+    if let Some(threads) = args.threads {
+        writeln!(out, "  Runtime threads: {threads}").expect("write to String");
+    }
+    if let Some(max_in_flight) = args.max_in_flight {
+        writeln!(out, "  Max in flight: {max_in_flight} requests").expect("write to String");
+    }
+    if let Some(think_time) = args.think_time {
+        writeln!(out, "  Think time: {} seconds", think_time.as_secs_f64())
+            .expect("write to String");
+    }
+    if let Some(warmup) = args.warmup {
+        writeln!(out, "  Warm-up: {} seconds", warmup.as_secs_f64()).expect("write to String");
+    }
+    if let Some(ramp_up) = args.ramp_up {
+        writeln!(out, "  Ramp-up: {} seconds", ramp_up.as_secs_f64()).expect("write to String");
+    }
+    if let Some(ramp_down) = args.ramp_down {
+        writeln!(out, "  Ramp-down: {} seconds", ramp_down.as_secs_f64()).expect("write to String");
+    }
+    if let Some(requests) = args.requests {
+        writeln!(out, "  Requests: {requests}").expect("write to String");
+        if !args.duration.is_zero() {
+            writeln!(
+                out,
+                "  Expected duration: {} seconds",
+                args.duration.as_secs_f64()
+            )
+            .expect("write to String");
+        }
+    } else {
+        writeln!(
+            out,
+            "  Load test duration: {} seconds",
+            args.duration.as_secs()
+        )
+        .expect("write to String");
+    }
+    if let Some(drain_timeout) = args.drain_timeout {
+        writeln!(
+            out,
+            "  Drain timeout: {} seconds",
+            drain_timeout.as_secs_f64()
+        )
+        .expect("write to String");
+    }
+    if let Some(abort) = args.abort {
+        writeln!(
+            out,
+            "  Abort: above {}% errors over {} seconds",
+            abort.error_rate * 100.0_f64,
+            abort.window.as_secs_f64()
+        )
+        .expect("write to String");
+    }
     if let Some(max_time) = args.max_time {
         writeln!(out, "  Request timeout: {} seconds", max_time.as_secs())
             .expect("write to String");
@@ -74,9 +136,82 @@ fn format_args_timing(args: &Args) -> String {
     out
 }
 
+/// The load shape: stages, virtual users, a throughput search, an adaptive rate, or a fixed rate.
+fn format_args_load(args: &Args) -> String {
+    let mut out = String::new();
+    if let Some(ref stages) = args.stages {
+        out.push_str("  Stages:\n");
+        for stage in stages {
+            let secs = stage.duration.as_secs_f64();
+            match stage.target {
+                StageTarget::Rate(rps) => {
+                    writeln!(out, "    {secs} seconds @ {rps} requests/second")
+                        .expect("write to String");
+                }
+                StageTarget::Users(users) => {
+                    writeln!(out, "    {secs} seconds @ {users} virtual users")
+                        .expect("write to String");
+                }
+            }
+        }
+    } else if let Some(concurrency) = args.concurrency {
+        writeln!(out, "  Concurrency: {concurrency} virtual users").expect("write to String");
+    } else if let Some(search) = args.search {
+        writeln!(
+            out,
+            "  Search: from {} requests/second, {}% tolerance, up to {} probes of {} seconds",
+            args.requests_per_second,
+            search.tolerance * 100.0_f64,
+            crate::search::MAX_PROBES,
+            args.duration.as_secs_f64()
+        )
+        .expect("write to String");
+        if let Some(p99) = search.slo_p99 {
+            writeln!(out, "  SLO p99: {} seconds", p99.as_secs_f64()).expect("write to String");
+        }
+        if let Some(error_rate) = search.slo_error_rate {
+            writeln!(out, "  SLO error rate: {}%", error_rate * 100.0_f64)
+                .expect("write to String");
+        }
+    } else if let Some(adaptive) = args.adaptive {
+        writeln!(
+            out,
+            "  Adaptive: hold p{} at {} seconds, adjusting every {} seconds from {} requests/second",
+            adaptive.percentile * 100.0_f64,
+            adaptive.target_latency.as_secs_f64(),
+            adaptive.interval.as_secs_f64(),
+            args.requests_per_second
+        )
+        .expect("write to String");
+    } else {
+        writeln!(
+            out,
+            "  Throughput: {} requests/second",
+            args.requests_per_second
+        )
+        .expect("write to String");
+    }
+    out
+}
+
 fn format_args_output(args: &Args) -> String {
     let mut out = String::new();
     writeln!(out, "  Output file: {}", args.output.display()).expect("write to String");
+    if let Some(ref path) = args.events {
+        writeln!(out, "  Events file: {}", path.display()).expect("write to String");
+    }
+    writeln!(
+        out,
+        "  Histogram precision: {} significant digits",
+        args.histogram_precision
+    )
+    .expect("write to String");
+    writeln!(
+        out,
+        "  Snapshot interval: {} seconds",
+        args.snapshot_interval.as_secs_f64()
+    )
+    .expect("write to String");
     writeln!(out, "  Protocol: {}", args.protocol.as_ref()).expect("write to String");
     let payload_size = match args.payload {
         Some(Payload::Data(ref data)) => data.len(),
@@ -93,7 +228,12 @@ fn format_args_output(args: &Args) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cli::{Args, HttpProtocol, Payload};
+    use crate::abort::AbortPolicy;
+    use crate::adaptive::Adaptive;
+    use crate::assertion::Assertion;
+    use crate::cli::{Args, ArrivalProcess, HttpProtocol, Payload};
+    use crate::search::Search;
+    use crate::stages::Stage;
     use bytes::Bytes;
     use std::path::PathBuf;
     use std::time::Duration;
@@ -109,10 +249,31 @@ mod tests {
             key: None,
             location: false,
             requests_per_second: rust_decimal::Decimal::from(1),
+            concurrency: None,
+            think_time: None,
+            ramp_up: None,
+            ramp_down: None,
+            stages: None,
+            arrival: ArrivalProcess::Constant,
+            seed: 0,
+            max_in_flight: None,
+            workers: None,
+            threads: None,
+            warmup: None,
+            requests: None,
+            search: None,
+            adaptive: None,
+            abort: None,
             duration: Duration::from_secs(10),
+            drain_timeout: None,
+            assertions: Vec::new(),
+            expect_body_max_bytes: 1_048_576,
             max_time: None,
             connect_timeout: None,
             output: PathBuf::from("report.pb"),
+            events: None,
+            histogram_precision: 3,
+            snapshot_interval: Duration::from_millis(250),
             protocol: HttpProtocol::Http1_1,
             payload: None,
             identity: None,
@@ -179,6 +340,8 @@ mod tests {
         "  Throughput: 1 requests/second\n",
         "  Load test duration: 10 seconds\n",
         "  Output file: report.pb\n",
+        "  Histogram precision: 3 significant digits\n",
+        "  Snapshot interval: 0.25 seconds\n",
         "  Protocol: HTTP/1.1\n",
         "  Request body size: 0 bytes\n",
     );
@@ -204,6 +367,15 @@ mod tests {
         format_args_request_insecure_false: minimal_args("https://example.com") => (contains "  Insecure: false\n");
         format_args_request_insecure_true: { let mut a = minimal_args("https://example.com"); a.insecure = true; a } => (contains "  Insecure: true\n");
         format_args_request_method_get: minimal_args("https://example.com") => (contains "  Method: GET\n");
+        format_args_request_assertions_none: minimal_args("https://example.com") => (not_contains "Assertions:");
+        format_args_request_assertions_some: {
+            let mut a = minimal_args("https://example.com");
+            a.assertions = vec![
+                Assertion::Status(vec![200, 204]),
+                Assertion::BodyContains("ok".to_owned()),
+            ];
+            a
+        } => (contains "  Assertions:\n    status in 200,204\n    body contains \"ok\"\n");
         format_args_request_method_post: { let mut a = minimal_args("https://example.com"); a.request = http::Method::POST; a } => (contains "  Method: POST\n");
         format_args_request_method_put: { let mut a = minimal_args("https://example.com"); a.request = http::Method::PUT; a } => (contains "  Method: PUT\n");
         format_args_request_method_delete: { let mut a = minimal_args("https://example.com"); a.request = http::Method::DELETE; a } => (contains "  Method: DELETE\n");
@@ -225,13 +397,56 @@ mod tests {
         format_args_timing_follow_redirects_true: { let mut a = minimal_args("https://example.com"); a.location = true; a } => (contains "  Follow redirects: true\n");
         format_args_timing_throughput: { let mut a = minimal_args("https://example.com"); a.requests_per_second = rust_decimal::Decimal::from(100); a } => (contains "  Throughput: 100 requests/second\n");
         format_args_timing_throughput_decimal: { let mut a = minimal_args("https://example.com"); a.requests_per_second = rust_decimal::Decimal::try_from(0.5).unwrap(); a } => (contains "requests/second"), (contains "0.5");
-        format_args_timing_duration: { let mut a = minimal_args("https://example.com"); a.duration = Duration::from_secs(60); a } => (contains "  Load test duration: 60 seconds\n");
+        format_args_timing_concurrency: { let mut a = minimal_args("https://example.com"); a.concurrency = Some(16); a } => (contains "  Concurrency: 16 virtual users\n"), (not_contains "Throughput:"), (not_contains "Think time:");
+        format_args_timing_think_time: { let mut a = minimal_args("https://example.com"); a.concurrency = Some(2); a.think_time = Some(Duration::from_millis(250)); a } => (contains "  Think time: 0.25 seconds\n");
+        format_args_timing_constant_arrival_hidden: minimal_args("https://example.com") => (not_contains "Arrival:");
+        format_args_timing_poisson_arrival: { let mut a = minimal_args("https://example.com"); a.arrival = ArrivalProcess::Poisson; a.seed = 42; a } => (contains "  Arrival: poisson (seed 42)\n");
+        format_args_timing_workers_and_threads: { let mut a = minimal_args("https://example.com"); a.workers = Some(4); a.threads = Some(2); a } => (contains "  Workers: 4\n"), (contains "  Runtime threads: 2\n");
+        format_args_timing_max_in_flight: { let mut a = minimal_args("https://example.com"); a.max_in_flight = Some(64); a } => (contains "  Max in flight: 64 requests\n");
+        format_args_timing_drain_timeout: { let mut a = minimal_args("https://example.com"); a.drain_timeout = Some(Duration::from_millis(1500)); a } => (contains "  Drain timeout: 1.5 seconds\n");
+        format_args_timing_warmup: { let mut a = minimal_args("https://example.com"); a.warmup = Some(Duration::from_secs(5)); a } => (contains "  Warm-up: 5 seconds\n");
+        format_args_timing_ramps_none: minimal_args("https://example.com") => (not_contains "Ramp-up:"), (not_contains "Ramp-down:");
+        format_args_timing_ramps_some: { let mut a = minimal_args("https://example.com"); a.ramp_up = Some(Duration::from_secs(30)); a.ramp_down = Some(Duration::from_millis(2500)); a } => (contains "  Ramp-up: 30 seconds\n"), (contains "  Ramp-down: 2.5 seconds\n");
+        format_args_timing_stages: {
+            let mut a = minimal_args("https://example.com");
+            a.stages = Some(vec![
+                Stage { duration: Duration::from_mins(1), target: StageTarget::Rate(rust_decimal::Decimal::from(100)) },
+                Stage { duration: Duration::from_secs(30), target: StageTarget::Rate(rust_decimal::Decimal::ZERO) },
+            ]);
+            a
+        } => (contains "  Stages:\n    60 seconds @ 100 requests/second\n    30 seconds @ 0 requests/second\n"), (not_contains "Throughput:");
+        format_args_timing_user_stages: {
+            let mut a = minimal_args("https://example.com");
+            a.stages = Some(vec![Stage { duration: Duration::from_millis(1500), target: StageTarget::Users(20) }]);
+            a
+        } => (contains "    1.5 seconds @ 20 virtual users\n");
+        format_args_timing_duration: { let mut a = minimal_args("https://example.com"); a.duration = Duration::from_mins(1); a } => (contains "  Load test duration: 60 seconds\n");
+        format_args_timing_requests_open_loop: { let mut a = minimal_args("https://example.com"); a.requests = Some(100); a.duration = Duration::from_millis(2500); a } => (contains "  Requests: 100\n"), (contains "  Expected duration: 2.5 seconds\n"), (not_contains "Load test duration:");
+        format_args_timing_requests_closed_loop: { let mut a = minimal_args("https://example.com"); a.concurrency = Some(4); a.requests = Some(100); a.duration = Duration::ZERO; a } => (contains "  Requests: 100\n"), (not_contains "duration:");
+        format_args_timing_search: {
+            let mut a = minimal_args("https://example.com");
+            a.requests_per_second = rust_decimal::Decimal::from(50);
+            a.search = Some(Search { slo_p99: Some(Duration::from_millis(200)), slo_error_rate: Some(0.01), tolerance: 0.05 });
+            a
+        } => (contains "  Search: from 50 requests/second, 5% tolerance, up to 16 probes of 10 seconds\n"), (contains "  SLO p99: 0.2 seconds\n"), (contains "  SLO error rate: 1%\n"), (not_contains "Throughput:");
+        format_args_timing_adaptive: {
+            let mut a = minimal_args("https://example.com");
+            a.requests_per_second = rust_decimal::Decimal::from(50);
+            a.adaptive = Some(Adaptive { target_latency: Duration::from_millis(200), percentile: 0.99, interval: Duration::from_secs(1) });
+            a
+        } => (contains "  Adaptive: hold p99 at 0.2 seconds, adjusting every 1 seconds from 50 requests/second\n"), (not_contains "Throughput:");
+        format_args_timing_abort_none: minimal_args("https://example.com") => (not_contains "Abort:");
+        format_args_timing_abort_some: { let mut a = minimal_args("https://example.com"); a.abort = Some(AbortPolicy { error_rate: 0.5, window: Duration::from_secs(10) }); a } => (contains "  Abort: above 50% errors over 10 seconds\n");
         format_args_timing_max_time_none: minimal_args("https://example.com") => (not_contains "Request timeout:");
         format_args_timing_max_time_some: { let mut a = minimal_args("https://example.com"); a.max_time = Some(Duration::from_secs(30)); a } => (contains "  Request timeout: 30 seconds\n");
         format_args_timing_connect_timeout_none: minimal_args("https://example.com") => (not_contains "Connection timeout:");
         format_args_timing_connect_timeout_some: { let mut a = minimal_args("https://example.com"); a.connect_timeout = Some(Duration::from_secs(5)); a } => (contains "  Connection timeout: 5 seconds\n");
 
         format_args_output_file_path: { let mut a = minimal_args("https://example.com"); a.output = PathBuf::from("/var/report.pb"); a } => (contains "  Output file: /var/report.pb\n");
+        format_args_output_histogram_precision: minimal_args("https://example.com") => (contains "  Histogram precision: 3 significant digits\n");
+        format_args_output_snapshot_interval: { let mut a = minimal_args("https://example.com"); a.snapshot_interval = Duration::from_secs(1); a } => (contains "  Snapshot interval: 1 seconds\n");
+        format_args_output_events_none: minimal_args("https://example.com") => (not_contains "Events file:");
+        format_args_output_events_some: { let mut a = minimal_args("https://example.com"); a.events = Some(PathBuf::from("/tmp/events.pb")); a } => (contains "  Events file: /tmp/events.pb\n");
         format_args_output_protocol_http1_1: minimal_args("https://example.com") => (contains "  Protocol: HTTP/1.1\n");
         format_args_output_protocol_http2: { let mut a = minimal_args("https://example.com"); a.protocol = HttpProtocol::Http2; a } => (contains "  Protocol: HTTP/2\n");
         format_args_output_payload_none: minimal_args("https://example.com") => (contains "  Request body size: 0 bytes\n");
@@ -266,7 +481,7 @@ mod tests {
         args.key = Some(PathBuf::from("key.pem"));
         args.location = true;
         args.requests_per_second = rust_decimal::Decimal::from(10);
-        args.duration = Duration::from_secs(120);
+        args.duration = Duration::from_mins(2);
         args.max_time = Some(Duration::from_secs(5));
         args.connect_timeout = Some(Duration::from_secs(3));
         args.output = PathBuf::from("out.pb");
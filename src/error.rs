@@ -51,14 +51,17 @@ pub enum AppError {
     #[diagnostic(code(loadtest::failed_to_build_client))]
     FailedToBuildClient { source: reqwest::Error },
 
+    /// The TLS phase needs its own rustls crypto provider, but another one was installed first.
+    #[error(
+        "Another rustls crypto provider is already installed, so TLS handshakes cannot be timed"
+    )]
+    #[diagnostic(code(loadtest::tls_provider_installed))]
+    TlsProviderInstalled,
+
     #[error("Failed to send request")]
     #[diagnostic(code(loadtest::failed_to_send_request))]
     FailedToSendRequest { source: reqwest::Error },
 
-    #[error("Failed to get response text")]
-    #[diagnostic(code(loadtest::failed_to_get_response_text))]
-    FailedToGetResponseChunk { source: reqwest::Error },
-
     #[error("I/O error: {0}")]
     #[diagnostic(code(loadtest::io))]
     Io(#[from] std::io::Error),
@@ -75,6 +78,88 @@ pub enum AppError {
     #[diagnostic(code(loadtest::specify_protocol))]
     SpecifyProtocol,
 
+    /// A duration flag was given a negative, infinite or out-of-range number of seconds.
+    #[error("{flag} must be a non-negative number of seconds, got {value}")]
+    #[diagnostic(code(loadtest::invalid_seconds))]
+    InvalidSeconds {
+        flag: &'static str,
+        value: f64,
+        #[source]
+        source: std::time::TryFromFloatSecsError,
+    },
+
+    #[error("--ramp-up and --ramp-down together must not exceed --duration")]
+    #[diagnostic(code(loadtest::ramps_exceed_duration))]
+    RampsExceedDuration,
+
+    #[error("--requests needs a positive --requests-per-second (or --concurrency)")]
+    #[diagnostic(code(loadtest::requests_need_rate))]
+    RequestsNeedRate,
+
+    #[error("--ramp-up must end before --requests have all been sent")]
+    #[diagnostic(code(loadtest::ramp_up_exceeds_requests))]
+    RampUpExceedsRequests,
+
+    #[error("Percentages must be between 0 and 100")]
+    #[diagnostic(code(loadtest::invalid_percentage))]
+    InvalidPercentage,
+
+    #[error("--search needs a positive --requests-per-second to start from")]
+    #[diagnostic(code(loadtest::search_needs_rate))]
+    SearchNeedsRate,
+
+    #[error("--target-latency needs a positive --requests-per-second to start from")]
+    #[diagnostic(code(loadtest::adaptive_needs_rate))]
+    AdaptiveNeedsRate,
+
+    #[error("--adapt-interval must be positive")]
+    #[diagnostic(code(loadtest::zero_adapt_interval))]
+    ZeroAdaptInterval,
+
+    #[error("--abort-on-error-rate must be a fraction between 0 and 1")]
+    #[diagnostic(code(loadtest::invalid_abort_error_rate))]
+    InvalidAbortErrorRate,
+
+    #[error("--abort-window must be positive")]
+    #[diagnostic(code(loadtest::zero_abort_window))]
+    ZeroAbortWindow,
+
+    #[error("--snapshot-interval must be at least 0.01 seconds")]
+    #[diagnostic(code(loadtest::snapshot_interval_too_short))]
+    SnapshotIntervalTooShort,
+
+    /// Regular expression given to --expect-body-regex does not compile.
+    #[error("Invalid regular expression {raw:?}")]
+    #[diagnostic(code(loadtest::invalid_regex))]
+    InvalidRegex { raw: String, source: regex::Error },
+
+    /// --expect-json is not `POINTER=VALUE`.
+    #[error("Invalid JSON assertion {raw:?} expected POINTER=VALUE (e.g. \"/status=ok\")")]
+    #[diagnostic(code(loadtest::invalid_json_assertion))]
+    InvalidJsonAssertion { raw: String },
+
+    #[error("--warmup must be shorter than --duration")]
+    #[diagnostic(code(loadtest::warmup_exceeds_duration))]
+    WarmupExceedsDuration,
+
+    /// Stage is not `DURATION@TARGET`.
+    #[error("Invalid stage {raw:?} expected DURATION@TARGET (e.g. \"1m@100rps\" or \"30s@20vu\")")]
+    #[diagnostic(code(loadtest::invalid_stage))]
+    InvalidStage { raw: String },
+
+    #[error("Stages file does not contain any stage")]
+    #[diagnostic(code(loadtest::no_stages))]
+    NoStages,
+
+    #[error("Stages must either all be request rates or all be virtual users")]
+    #[diagnostic(code(loadtest::mixed_stage_targets))]
+    MixedStageTargets,
+
+    /// --arrival or --max-in-flight given with virtual-user stages, which run closed-loop.
+    #[error("--arrival and --max-in-flight only apply to request-rate stages, not virtual users")]
+    #[diagnostic(code(loadtest::open_loop_flag_with_user_stages))]
+    OpenLoopFlagWithUserStages,
+
     #[error("upload-file and data are mutually exclusive")]
     #[diagnostic(code(loadtest::mutually_exclusive_upload_file_and_data))]
     MutuallyExclusiveUploadFileAndData,
@@ -93,6 +178,13 @@ pub enum AppError {
         source: std::io::Error,
     },
 
+    #[error("Failed to write file {path:?}")]
+    #[diagnostic(code(loadtest::failed_to_write_file))]
+    FailedToWriteFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
     #[error("If --key is provided, --cert must also be provided")]
     #[diagnostic(code(loadtest::cert_must_be_provided_with_key))]
     CertMustBeProvidedWithKey,
@@ -0,0 +1,236 @@
+//! Per-request event log (--events).
+//!
+//! Every completed request is written out as one length-delimited `RequestResult`, for offline
+//! analysis beyond the report's fixed CDFs. Workers only hand records to a channel; a single writer
+//! task encodes them and writes them through a buffer, so that disk I/O stays off the request path.
+//! The channel is bounded: when the writer falls behind, records are left out and counted rather
+//! than held in memory or allowed to slow the workers down.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Instant, SystemTime},
+};
+
+use prost::Message as _;
+use tokio::{
+    fs::File,
+    io::{AsyncWriteExt as _, BufWriter},
+    sync::mpsc::{self, Receiver, Sender, error::TrySendError},
+    task::JoinHandle,
+};
+
+use crate::{
+    error::{AppError, Result},
+    proto::RequestResult,
+    report,
+    work_unit::ExecutionResult,
+};
+
+/// Records the writer takes off the channel at a time.
+const BATCH: usize = 1024;
+
+/// Records waiting for the writer, beyond which new ones are left out.
+const CAPACITY: usize = 64 * BATCH;
+
+/// The open event file and the task writing to it.
+#[derive(Debug)]
+pub(crate) struct EventLog {
+    path: PathBuf,
+    sender: Sender<RequestResult>,
+    /// Records left out because the channel was full.
+    dropped: Arc<AtomicU64>,
+    writer: JoinHandle<std::io::Result<()>>,
+}
+
+impl EventLog {
+    /// Creates (or truncates) the file at `path` and starts writing the records sent to it.
+    pub(crate) async fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .await
+            .map_err(|source| AppError::FailedToOpenFile {
+                path: path.to_owned(),
+                source,
+            })?;
+        let (sender, receiver) = mpsc::channel(CAPACITY);
+        Ok(Self {
+            path: path.to_owned(),
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+            writer: tokio::spawn(write(file, receiver)),
+        })
+    }
+
+    /// Where a run's workers send their records.
+    pub(crate) fn recorder(&self) -> EventRecorder {
+        EventRecorder {
+            sender: self.sender.clone(),
+            dropped: Arc::clone(&self.dropped),
+            clock: (SystemTime::now(), Instant::now()),
+        }
+    }
+
+    /// Records left out so far because the writer fell behind.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Waits until every record has been written. Every recorder must have been dropped.
+    pub(crate) async fn finish(self) -> Result<()> {
+        drop(self.sender);
+        self.writer
+            .await?
+            .map_err(|source| AppError::FailedToWriteFile {
+                path: self.path,
+                source,
+            })
+    }
+}
+
+/// Sends the records of one run to the event log.
+#[derive(Debug, Clone)]
+pub(crate) struct EventRecorder {
+    sender: Sender<RequestResult>,
+    dropped: Arc<AtomicU64>,
+    /// The same moment on the wall clock and on the monotonic clock, to place requests in time.
+    clock: (SystemTime, Instant),
+}
+
+impl EventRecorder {
+    /// Records a completed request of worker `worker_id`.
+    pub(crate) fn record(&self, worker_id: usize, warmup: bool, result: &ExecutionResult) {
+        let mut event = report::map_request_to_proto(result);
+        event.worker_id = u32::try_from(worker_id).expect("worker id fits in u32");
+        event.warmup = warmup;
+        event.scheduled_unix_nanos = self.unix_nanos(result.scheduled);
+        event.started_unix_nanos = self.unix_nanos(result.started);
+        event.timestamp_unix_nanos = self.unix_nanos(
+            result
+                .started
+                .checked_add(result.duration)
+                .expect("request end overflow"),
+        );
+        // The writer only hangs up after a write error, which `EventLog::finish` reports.
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(event) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn unix_nanos(&self, at: Instant) -> i64 {
+        let (wall, monotonic) = self.clock;
+        let wall = match at.checked_duration_since(monotonic) {
+            Some(since) => wall.checked_add(since),
+            None => wall.checked_sub(monotonic.saturating_duration_since(at)),
+        };
+        report::system_time_to_unix_nanos(wall.expect("request time fits in SystemTime"))
+    }
+}
+
+/// Writes `events` to `file`, length-delimited, until every sender is gone.
+async fn write(file: File, mut events: Receiver<RequestResult>) -> std::io::Result<()> {
+    let mut out = BufWriter::new(file);
+    let mut batch = Vec::with_capacity(BATCH);
+    let mut encoded = Vec::new();
+    while events.recv_many(&mut batch, BATCH).await > 0 {
+        encoded.clear();
+        for event in batch.drain(..) {
+            event
+                .encode_length_delimited(&mut encoded)
+                .expect("a Vec grows to fit the record");
+        }
+        out.write_all(&encoded).await?;
+    }
+    out.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::RequestOutcome;
+    use crate::work_unit::{HttpRequestOutcome, Phases};
+    use std::time::Duration;
+
+    /// A 503 that took 20 ms, sent 5 ms after it was due.
+    fn result() -> ExecutionResult {
+        let scheduled = Instant::now();
+        ExecutionResult {
+            outcome: HttpRequestOutcome::ServerErrorResponse,
+            status_code: Some(503),
+            duration: Duration::from_millis(20),
+            time_to_headers: None,
+            scheduled,
+            started: scheduled + Duration::from_millis(5),
+            phases: Phases::default(),
+            request_bytes: 3,
+            response_bytes: 42,
+            failed_assertions: Vec::new(),
+            failure: None,
+        }
+    }
+
+    /// Records in the event file at `path`, which is removed.
+    fn read_events(path: &Path) -> Vec<RequestResult> {
+        let bytes = std::fs::read(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let mut buffer = bytes.as_slice();
+        let mut events = Vec::new();
+        while !buffer.is_empty() {
+            events.push(RequestResult::decode_length_delimited(&mut buffer).unwrap());
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn writes_one_length_delimited_record_per_request() {
+        let path = std::env::temp_dir().join(format!("loadtest-events-{}.pb", std::process::id()));
+        let log = EventLog::create(&path).await.unwrap();
+        let recorder = log.recorder();
+        let result = result();
+        recorder.record(2, false, &result);
+        recorder.record(0, true, &result);
+        drop(recorder);
+        log.finish().await.unwrap();
+
+        let events = read_events(&path);
+        let [ref first, ref second] = events[..] else {
+            panic!("expected two records: {events:?}");
+        };
+        assert_eq!(first.worker_id, 2);
+        assert!(!first.warmup);
+        assert_eq!(first.status_code, 503);
+        assert_eq!(first.outcome(), RequestOutcome::ServerError);
+        assert!(!first.success);
+        assert_eq!(first.latency_nanos, 20_000_000);
+        assert_eq!((first.request_bytes, first.response_bytes), (3, 42));
+        assert_eq!(
+            first.started_unix_nanos - first.scheduled_unix_nanos,
+            5_000_000
+        );
+        assert_eq!(
+            first.timestamp_unix_nanos - first.started_unix_nanos,
+            20_000_000
+        );
+        assert_eq!(second.worker_id, 0);
+        assert!(second.warmup);
+    }
+
+    #[tokio::test]
+    async fn records_beyond_the_capacity_are_dropped_and_counted() {
+        let path =
+            std::env::temp_dir().join(format!("loadtest-events-full-{}.pb", std::process::id()));
+        let log = EventLog::create(&path).await.unwrap();
+        let recorder = log.recorder();
+        let result = result();
+        // The writer gets no chance to run in between: the channel fills up.
+        for _ in 0..CAPACITY + 5 {
+            recorder.record(0, false, &result);
+        }
+        drop(recorder);
+        assert_eq!(log.dropped(), 5);
+        log.finish().await.unwrap();
+        assert_eq!(read_events(&path).len(), CAPACITY);
+    }
+}
@@ -0,0 +1,202 @@
+//! Bounded-memory distributions of latencies and sizes.
+//!
+//! Every request adds to several distributions, over runs that can last hours at tens of thousands
+//! of requests per second. Rather than keeping every value, values are counted in HDR histograms:
+//! log-linear buckets that keep a set number of significant decimal digits (--histogram-precision),
+//! in memory that depends only on the range of the values. Histograms merge by adding counts.
+//!
+//! A bucket is reported as the value recorded in it when all its values were the same, so that
+//! exact inputs come back exact; otherwise as the middle of the bucket.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use hdrhistogram::Histogram as Hdr;
+
+/// Significant decimal digits kept when --histogram-precision is not given.
+pub const DEFAULT_PRECISION: u8 = 3;
+
+/// Counts of values (nanoseconds for latencies, bytes for sizes), accurate to the precision given
+/// at creation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    counts: Hdr<u64>,
+    /// For each bucket holding values (by its lowest value): the value recorded in it, or `None`
+    /// once two different values were.
+    exact: BTreeMap<u64, Option<u64>>,
+}
+
+impl Histogram {
+    /// An empty histogram keeping `precision` significant decimal digits (0 to 5).
+    pub fn new(precision: u8) -> Self {
+        Self {
+            counts: Hdr::new(precision).expect("histogram precision is between 0 and 5"),
+            exact: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, value: u64) {
+        self.counts
+            .record(value)
+            .expect("a growing histogram takes any value");
+        let bucket = self.counts.lowest_equivalent(value);
+        let exact = self.exact.entry(bucket).or_insert(Some(value));
+        if *exact != Some(value) {
+            *exact = None;
+        }
+    }
+
+    pub fn record_duration(&mut self, duration: Duration) {
+        self.record(u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX));
+    }
+
+    /// Adds the counts of `other`.
+    pub fn merge(&mut self, other: &Self) {
+        self.counts
+            .add(&other.counts)
+            .expect("a growing histogram takes any value");
+        for (&bucket, &value) in &other.exact {
+            let exact = self.exact.entry(bucket).or_insert(value);
+            if *exact != value {
+                *exact = None;
+            }
+        }
+    }
+
+    /// Values recorded.
+    pub fn len(&self) -> u64 {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Value at quantile `q` (in [0, 1]), as its bucket is reported.
+    pub fn quantile(&self, q: f64) -> u64 {
+        self.reported(self.counts.value_at_quantile(q))
+    }
+
+    /// Highest value recorded, to the histogram's precision.
+    pub fn max(&self) -> u64 {
+        self.reported(self.counts.max())
+    }
+
+    /// Returns the counts, leaving the histogram empty.
+    pub fn take(&mut self) -> Self {
+        let empty = Hdr::new_from(&self.counts);
+        Self {
+            counts: std::mem::replace(&mut self.counts, empty),
+            exact: std::mem::take(&mut self.exact),
+        }
+    }
+
+    /// `(value, count)` of every bucket that holds values, in increasing order of value. A bucket's
+    /// value is the one recorded in it if they were all the same, otherwise the middle of the
+    /// bucket.
+    pub fn buckets(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.counts.iter_recorded().map(|bucket| {
+            (
+                self.reported(bucket.value_iterated_to()),
+                bucket.count_at_value(),
+            )
+        })
+    }
+
+    /// How the bucket holding `value` is reported.
+    fn reported(&self, value: u64) -> u64 {
+        let bucket = self.counts.lowest_equivalent(value);
+        match self.exact.get(&bucket) {
+            Some(&Some(exact)) => exact,
+            Some(&None) | None => self.counts.median_equivalent(value),
+        }
+    }
+}
+
+impl Default for Histogram {
+    #[inline]
+    fn default() -> Self {
+        Self::new(DEFAULT_PRECISION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_values_are_exact() {
+        let mut histogram = Histogram::default();
+        for value in [3, 1, 3, 1_000] {
+            histogram.record(value);
+        }
+        assert_eq!(
+            histogram.buckets().collect::<Vec<_>>(),
+            [(1, 1), (3, 2), (1_000, 1)]
+        );
+        assert_eq!(histogram.len(), 4);
+    }
+
+    #[test]
+    fn large_values_keep_the_precision() {
+        for precision in 1..=5 {
+            let mut histogram = Histogram::new(precision);
+            // Two values in one bucket: it is reported as its middle.
+            histogram.record_duration(Duration::from_millis(1_234));
+            histogram.record_duration(Duration::from_nanos(1_234_000_001));
+            let (value, _) = histogram.buckets().next().unwrap();
+            let error = value.abs_diff(1_234_000_000) as f64 / 1_234_000_000.0;
+            assert!(
+                error < 10_f64.powi(-i32::from(precision)),
+                "{precision}: {value}"
+            );
+        }
+    }
+
+    #[test]
+    fn values_alone_in_their_bucket_are_exact() {
+        let mut histogram = Histogram::default();
+        for millis in [10, 10, 20, 30] {
+            histogram.record_duration(Duration::from_millis(millis));
+        }
+        assert_eq!(
+            histogram.buckets().collect::<Vec<_>>(),
+            [(10_000_000, 2), (20_000_000, 1), (30_000_000, 1)]
+        );
+        assert_eq!(histogram.quantile(0.5), 10_000_000);
+        assert_eq!(histogram.max(), 30_000_000);
+
+        // Merging in another value of the same bucket makes it approximate again.
+        let mut other = Histogram::default();
+        other.record(30_000_001);
+        histogram.merge(&other);
+        let max = histogram.max();
+        assert_ne!(max, 30_000_000);
+        assert!(max.abs_diff(30_000_000) < 30_000, "{max}");
+    }
+
+    #[test]
+    fn quantiles_and_take() {
+        let mut histogram = Histogram::default();
+        for value in 1..=100 {
+            histogram.record(value);
+        }
+        assert_eq!(histogram.quantile(0.5), 50);
+        assert_eq!(histogram.quantile(0.99), 99);
+        assert_eq!(histogram.max(), 100);
+        let taken = histogram.take();
+        assert_eq!(taken.len(), 100);
+        assert!(histogram.is_empty());
+    }
+
+    #[test]
+    fn merge_adds_counts() {
+        let mut first = Histogram::default();
+        let mut second = Histogram::default();
+        first.record(10);
+        second.record(10);
+        second.record(5_000_000_000);
+        first.merge(&second);
+        assert_eq!(first.len(), 3);
+        assert_eq!(first.buckets().next(), Some((10, 2)));
+    }
+}
@@ -104,25 +104,69 @@
     clippy::verbose_file_reads,
     clippy::wildcard_enum_match_arm
 )]
+#![cfg_attr(
+    test,
+    allow(
+        clippy::unwrap_used,
+        clippy::panic,
+        clippy::indexing_slicing,
+        clippy::missing_asserts_for_indexing,
+        clippy::arithmetic_side_effects,
+        clippy::as_conversions,
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::default_numeric_fallback,
+        clippy::float_cmp,
+        clippy::unreadable_literal,
+        clippy::decimal_literal_representation,
+        clippy::let_underscore_must_use,
+        clippy::let_underscore_untyped,
+        clippy::too_many_lines,
+        reason = "tests fail loudly on purpose and spell out their data literally"
+    )
+)]
 
+mod abort;
+mod adaptive;
+mod arrival;
+mod assertion;
 mod cdf;
 mod cli;
+mod dispatcher;
 mod display;
 mod error;
+mod events;
+mod histogram;
+mod phases;
+mod profile;
 mod proto;
 mod report;
+mod reservoir;
+mod search;
+mod stages;
 mod stats;
+mod timeline;
 mod work_unit;
 mod worker_manager;
 
 #[cfg(test)]
 mod report_fixtures;
 
-pub use cli::{Args, HttpProtocol, Payload, parse};
+pub use abort::AbortPolicy;
+pub use adaptive::Adaptive;
+pub use assertion::Assertion;
+pub use cli::{Args, ArrivalProcess, HttpProtocol, Payload, parse};
 pub use display::format_args;
 pub use error::{AppError, Result};
+pub use phases::time_tls;
+pub use search::Search;
+pub use stages::{Stage, StageTarget};
+
+use tokio_util::sync::CancellationToken;
 
 /// Entry point: run load test (spawn workers, write report). Call after parsing CLI.
+/// Ctrl-C or SIGTERM at any point (including the drain, or between search probes), or an error rate
+/// above --abort-on-error-rate, stops the run early; the report then covers the part that ran.
 /// If `args.dry_run` is true, the caller should only print [`format_args`] and return.
 ///
 /// # Errors
@@ -133,7 +177,80 @@ pub async fn run(args: Args) -> Result<()> {
     if args.dry_run {
         return Ok(());
     }
-    let result = worker_manager::spawn_workers(&args).await?;
+    // One signal handler for the whole run, so that no signal goes unheeded between its steps.
+    let interrupt = CancellationToken::new();
+    let signals = tokio::spawn(worker_manager::cancel_on_signal(interrupt.clone()));
+    let outcome = run_and_report(&args, &interrupt).await;
+    signals.abort();
+    outcome
+}
+
+/// Runs the load test (or the search) until done or `interrupt` is cancelled, then writes the report.
+/// The event log (--events) is finished whether or not that succeeds, so that the records written
+/// so far reach the file.
+async fn run_and_report(args: &Args, interrupt: &CancellationToken) -> Result<()> {
+    let events = match args.events {
+        Some(ref path) => Some(events::EventLog::create(path).await?),
+        None => None,
+    };
+    let outcome = run_and_write_report(args, events.as_ref(), interrupt).await;
+    let Some(events) = events else {
+        return outcome;
+    };
+    let dropped = events.dropped();
+    let finished = events.finish().await;
+    outcome?;
+    finished?;
+    if let Some(ref path) = args.events {
+        println!("Wrote request events to {}", path.display());
+        if dropped > 0 {
+            println!("  {dropped} events were left out: writing them fell behind");
+        }
+    }
+    Ok(())
+}
+
+/// Runs the load test (or the search), sending every request to `events` if given, and writes the
+/// report.
+async fn run_and_write_report(
+    args: &Args,
+    events: Option<&events::EventLog>,
+    interrupt: &CancellationToken,
+) -> Result<()> {
+    let result = match args.search {
+        Some(settings) => search::search(args, settings, events, interrupt).await?,
+        None => worker_manager::spawn_workers(args, events, interrupt).await?,
+    };
+    if let Some(ref search) = result.search {
+        match search.capacity {
+            Some(capacity) => println!("Capacity: {capacity} requests/second"),
+            None => println!("No probe met the SLOs"),
+        }
+    }
+    if result.status == worker_manager::RunStatus::Interrupted {
+        println!(
+            "Interrupted after {:.1} seconds; writing a partial report",
+            result.run_duration.as_secs_f64()
+        );
+    }
+    if let Some(ref abort) = result.abort {
+        println!(
+            "Aborted after {:.1} seconds: {abort}",
+            abort.elapsed.as_secs_f64()
+        );
+    }
+    if result.dropped_tickets > 0 {
+        println!(
+            "{} requests were not sent: the workers fell behind the schedule",
+            result.dropped_tickets
+        );
+    }
+    if result.dropped_feedback > 0 {
+        println!(
+            "{} response times were left out of rate control or the abort check: it fell behind",
+            result.dropped_feedback
+        );
+    }
     let args_clone = args.clone();
     let (bytes, path) = tokio::task::spawn_blocking(move || {
         let bytes = report::build_run_report(&args_clone, &result);
@@ -107,24 +107,38 @@ use std::io::Write as _;
 
 use console::{Term, style};
 
-use loadtest::{format_args, parse, run};
+use loadtest::{Args, format_args, parse, run, time_tls};
 
 fn main() -> miette::Result<()> {
-    run_async()?;
+    run_main()?;
     Ok(())
 }
 
-#[tokio::main]
-async fn run_async() -> loadtest::Result<()> {
-    let args = parse().await?;
+fn run_main() -> loadtest::Result<()> {
+    // Before anything can install another provider: the TLS phase depends on this one.
+    time_tls()?;
+    // Parsing only reads a few files; the load test gets its own runtime sized from the arguments.
+    let args = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(parse())?;
+    if !print_args(&args)? {
+        return Ok(());
+    }
+    let mut runtime = tokio::runtime::Builder::new_multi_thread();
+    if let Some(threads) = args.threads {
+        runtime.worker_threads(threads);
+    }
+    runtime.enable_all().build()?.block_on(run(args))
+}
+
+/// Prints the arguments; returns whether the load test should run (false for --dry-run).
+fn print_args(args: &Args) -> loadtest::Result<bool> {
     let mut term = Term::stdout();
-    let formatted = format_args(&args);
+    let formatted = format_args(args);
     writeln!(term, "{}", style("Arguments").bold())?;
     for line in formatted.lines().skip(1) {
         writeln!(term, "{line}")?;
     }
-    if args.dry_run {
-        return Ok(());
-    }
-    run(args).await
+    Ok(!args.dry_run)
 }
@@ -0,0 +1,333 @@
+//! Connection phase timings (DNS resolution, TLS handshake and connection setup) for each request.
+//!
+//! reqwest resolves names and opens connections in its connector, away from the request that needs
+//! them. A timing resolver and a timing connector layer record how long each took into a task-local
+//! slot, which [`track`] scopes around one request. A request served from a pooled connection
+//! records nothing.
+//!
+//! reqwest has no hook between the TCP connect and the TLS handshake. Instead, [`time_tls`]
+//! installs the process-wide rustls crypto provider reqwest builds its TLS config from, with key
+//! exchange groups that note when the `ClientHello` is built, right after the TCP connect: the
+//! handshake runs from there until the connector hands the connection over. `main` installs it
+//! before anything else can.
+
+use std::{
+    cell::RefCell,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{LazyLock, OnceLock},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use crate::error::{AppError, Result};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use rustls::{
+    NamedGroup, ProtocolVersion,
+    crypto::{ActiveKeyExchange, CompletedKeyExchange, SupportedKxGroup},
+    ffdhe_groups::FfdheGroup,
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+tokio::task_local! {
+    static CONNECTION: RefCell<ConnectionPhases>;
+}
+
+/// Connection phases recorded while a request was in flight.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ConnectionPhases {
+    /// Name resolution.
+    pub dns: Option<Duration>,
+    /// TLS handshake, for HTTPS.
+    pub tls: Option<Duration>,
+    /// Whole connection setup, name resolution and TLS handshake included.
+    pub setup: Option<Duration>,
+    /// When the TLS handshake started; cleared once the connection is ready.
+    pub tls_started: Option<Instant>,
+}
+
+/// Runs `future` (one request) and returns its output with the connection phases it went through.
+pub(crate) async fn track<F: Future>(future: F) -> (F::Output, ConnectionPhases) {
+    CONNECTION
+        .scope(RefCell::new(ConnectionPhases::default()), async move {
+            let output = future.await;
+            let phases = CONNECTION.with(|phases| *phases.borrow());
+            (output, phases)
+        })
+        .await
+}
+
+/// Updates the phases of the request being tracked, if any.
+fn record(update: impl FnOnce(&mut ConnectionPhases)) {
+    // Connections opened outside of `track` have no request to attribute them to.
+    CONNECTION
+        .try_with(|phases| update(&mut phases.borrow_mut()))
+        .unwrap_or_default();
+}
+
+/// A name that could not be resolved. Resolver failures reach the request error wrapped in this, so
+/// that they can be told apart from other connect errors.
+#[derive(Debug, thiserror::Error)]
+#[error("failed to resolve {name}")]
+pub(crate) struct ResolveError {
+    pub name: String,
+    #[source]
+    pub source: std::io::Error,
+}
+
+/// Resolves names with the system resolver, like reqwest's default one, and times it.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TimingResolver;
+
+impl Resolve for TimingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let started = Instant::now();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await
+                .map_err(|source| ResolveError {
+                    name: name.as_str().to_owned(),
+                    source,
+                })?
+                .collect();
+            record(|phases| phases.dns = Some(started.elapsed()));
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Connector layer timing the connection setup.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TimingLayer;
+
+impl<S> Layer<S> for TimingLayer {
+    type Service = TimingConnector<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimingConnector { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TimingConnector<S> {
+    inner: S,
+}
+
+impl<S, R> Service<R> for TimingConnector<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+    S::Response: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: R) -> Self::Future {
+        let started = Instant::now();
+        let connecting = self.inner.call(req);
+        Box::pin(async move {
+            let connection = connecting.await?;
+            record(|phases| {
+                phases.tls = phases.tls_started.take().map(|at| at.elapsed());
+                phases.setup = Some(started.elapsed());
+            });
+            Ok(connection)
+        })
+    }
+}
+
+/// Every key exchange group of the provider, wrapped once for the whole process.
+static TIMING_KX_GROUPS: LazyLock<Vec<TimingKxGroup>> = LazyLock::new(|| {
+    rustls::crypto::aws_lc_rs::ALL_KX_GROUPS
+        .iter()
+        .map(|&group| TimingKxGroup(group))
+        .collect()
+});
+
+/// Makes reqwest's TLS connections record when their handshake starts, for the TLS phase, by
+/// installing the process-wide rustls crypto provider. Fails if another provider was installed
+/// first; calling it again is fine.
+///
+/// # Errors
+///
+/// [`AppError::TlsProviderInstalled`] if another rustls crypto provider is already installed.
+#[inline]
+pub fn time_tls() -> Result<()> {
+    static INSTALLED: OnceLock<bool> = OnceLock::new();
+    let installed = *INSTALLED.get_or_init(|| {
+        let mut provider = rustls::crypto::aws_lc_rs::default_provider();
+        provider.kx_groups = provider
+            .kx_groups
+            .into_iter()
+            .map(|group| -> &'static dyn SupportedKxGroup {
+                TIMING_KX_GROUPS
+                    .iter()
+                    .find(|timing| timing.name() == group.name())
+                    .map_or(group, |timing| timing)
+            })
+            .collect();
+        provider.install_default().is_ok()
+    });
+    if installed {
+        Ok(())
+    } else {
+        Err(AppError::TlsProviderInstalled)
+    }
+}
+
+/// Key exchange group noting when a handshake starts: rustls starts a key exchange to build the
+/// `ClientHello`, the first message of the handshake.
+#[derive(Debug)]
+struct TimingKxGroup(&'static dyn SupportedKxGroup);
+
+impl SupportedKxGroup for TimingKxGroup {
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, rustls::Error> {
+        // A HelloRetryRequest or a TLS 1.2 server starts another one later in the same handshake.
+        record(|phases| {
+            phases.tls_started.get_or_insert_with(Instant::now);
+        });
+        self.0.start()
+    }
+
+    fn start_and_complete(
+        &self,
+        peer_pub_key: &[u8],
+    ) -> Result<CompletedKeyExchange, rustls::Error> {
+        self.0.start_and_complete(peer_pub_key)
+    }
+
+    fn ffdhe_group(&self) -> Option<FfdheGroup<'static>> {
+        self.0.ffdhe_group()
+    }
+
+    fn name(&self) -> NamedGroup {
+        self.0.name()
+    }
+
+    fn fips(&self) -> bool {
+        self.0.fips()
+    }
+
+    fn usable_for_version(&self, version: ProtocolVersion) -> bool {
+        self.0.usable_for_version(version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    use tokio::net::TcpListener;
+
+    /// Local keep-alive server answering every request with an empty 200.
+    async fn serve() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buffer = [0; 4096];
+                    while stream.read(&mut buffer).await.is_ok_and(|read| read > 0) {
+                        let reply = b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n";
+                        if stream.write_all(reply).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn new_connections_are_timed_and_pooled_ones_are_not() {
+        let port = serve().await;
+        let client = reqwest::Client::builder()
+            .tls_backend_rustls()
+            .dns_resolver(Arc::new(TimingResolver))
+            .connector_layer(TimingLayer)
+            .build()
+            .unwrap();
+        let url = format!("http://localhost:{port}/");
+
+        let (response, phases) = track(client.get(&url).send()).await;
+        response.unwrap().bytes().await.unwrap();
+        let (dns, setup) = (phases.dns.unwrap(), phases.setup.unwrap());
+        assert!(dns <= setup, "{dns:?} > {setup:?}");
+
+        let (response, phases) = track(client.get(&url).send()).await;
+        response.unwrap().bytes().await.unwrap();
+        assert_eq!(phases, ConnectionPhases::default());
+    }
+
+    #[tokio::test]
+    async fn connections_outside_a_request_are_ignored() {
+        let port = serve().await;
+        let client = reqwest::Client::builder()
+            .tls_backend_rustls()
+            .connector_layer(TimingLayer)
+            .build()
+            .unwrap();
+        let url = format!("http://127.0.0.1:{port}/");
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        response.bytes().await.unwrap();
+
+        // The connection opened untracked is pooled; the tracked request reusing it records nothing.
+        let (response, phases) = track(client.get(&url).send()).await;
+        response.unwrap().bytes().await.unwrap();
+        assert_eq!(phases, ConnectionPhases::default());
+    }
+
+    /// Stands in for reqwest's HTTPS connector: a TCP connect, then a TLS handshake starting with
+    /// a key exchange.
+    #[derive(Debug, Clone, Copy)]
+    struct Https {
+        tcp: Duration,
+        handshake: Duration,
+    }
+
+    impl Service<()> for Https {
+        type Response = ();
+        type Error = rustls::Error;
+        type Future = Pin<Box<dyn Future<Output = Result<(), rustls::Error>> + Send>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), rustls::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, (): ()) -> Self::Future {
+            let Self { tcp, handshake } = *self;
+            Box::pin(async move {
+                tokio::time::sleep(tcp).await;
+                drop(TimingKxGroup(rustls::crypto::aws_lc_rs::kx_group::X25519).start()?);
+                tokio::time::sleep(handshake).await;
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn tls_handshake_is_timed_from_its_first_key_exchange() {
+        let ms = Duration::from_millis;
+        let mut connector = TimingLayer.layer(Https {
+            tcp: ms(20),
+            handshake: ms(30),
+        });
+        let (connected, phases) = track(connector.call(())).await;
+        connected.unwrap();
+        let (tls, setup) = (phases.tls.unwrap(), phases.setup.unwrap());
+        assert!(tls >= ms(30) && setup >= tls + ms(20), "{tls:?}, {setup:?}");
+        assert!(phases.tls_started.is_none());
+    }
+}
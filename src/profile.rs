@@ -0,0 +1,224 @@
+//! Target request rate over the course of a run.
+//!
+//! A profile is a piecewise-linear function of elapsed time (requests per second). The open-loop
+//! scheduler integrates it to know how many requests should have been sent so far, and the report
+//! stores its points so the viewer can overlay the target rate on the achieved one.
+
+use std::time::Duration;
+
+/// Piecewise-linear target rate, as `(elapsed, requests_per_second)` points sorted by time.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RateProfile {
+    points: Vec<(Duration, f64)>,
+}
+
+impl RateProfile {
+    /// Linear ramp from 0 to `peak` over `ramp_up`, hold `peak`, then linear ramp back to 0 over the
+    /// last `ramp_down` of `duration`. Zero-length ramps start or end the run at full rate.
+    pub(crate) fn ramped(
+        peak: f64,
+        duration: Duration,
+        ramp_up: Duration,
+        ramp_down: Duration,
+    ) -> Self {
+        let mut points = Vec::with_capacity(4);
+        if ramp_up.is_zero() {
+            points.push((Duration::ZERO, peak));
+        } else {
+            points.push((Duration::ZERO, 0.0_f64));
+            points.push((ramp_up, peak));
+        }
+        if ramp_down.is_zero() {
+            points.push((duration, peak));
+        } else {
+            points.push((duration.saturating_sub(ramp_down), peak));
+            points.push((duration, 0.0_f64));
+        }
+        Self { points }
+    }
+
+    /// Holds each `(duration, rate)` step for its duration, one after another.
+    pub(crate) fn stepped(steps: impl IntoIterator<Item = (Duration, f64)>) -> Self {
+        let mut points = Vec::new();
+        let mut at = Duration::ZERO;
+        for (duration, rate) in steps {
+            points.push((at, rate));
+            at = at.saturating_add(duration);
+            points.push((at, rate));
+        }
+        Self { points }
+    }
+
+    pub(crate) fn points(&self) -> &[(Duration, f64)] {
+        &self.points
+    }
+
+    /// Number of requests the profile calls for between the start of the run and `elapsed`
+    /// (area under the rate curve). Fractional; callers decide how to round.
+    pub(crate) fn requests_until(&self, elapsed: Duration) -> f64 {
+        let mut total = 0.0_f64;
+        for segment in self.points.windows(2) {
+            let [(t0, r0), (t1, r1)] = *segment else {
+                continue;
+            };
+            if elapsed <= t0 {
+                break;
+            }
+            let span = t1.saturating_sub(t0).as_secs_f64();
+            let dt = elapsed.min(t1).saturating_sub(t0).as_secs_f64();
+            let r_end = if span > 0.0_f64 {
+                r0 + (r1 - r0) * dt / span
+            } else {
+                r1
+            };
+            total += f64::midpoint(r0, r_end) * dt;
+        }
+        total
+    }
+
+    /// Elapsed time at which the profile has called for `requests` requests (the inverse of
+    /// [`Self::requests_until`]), or `None` if it never does.
+    pub(crate) fn time_for_requests(&self, requests: f64) -> Option<Duration> {
+        let mut remaining = requests;
+        for segment in self.points.windows(2) {
+            let [(t0, r0), (t1, r1)] = *segment else {
+                continue;
+            };
+            if remaining <= 0.0_f64 {
+                return Some(t0);
+            }
+            let span = t1.saturating_sub(t0).as_secs_f64();
+            let area = f64::midpoint(r0, r1) * span;
+            if remaining <= area {
+                // Solve r0·dt + slope/2·dt² = remaining, written so it stays exact for a zero slope.
+                let slope = (r1 - r0) / span;
+                let dt =
+                    2.0_f64 * remaining / (r0 + 2.0_f64.mul_add(slope * remaining, r0 * r0).sqrt());
+                return Some(
+                    t0.saturating_add(Duration::try_from_secs_f64(dt).ok()?)
+                        .min(t1),
+                );
+            }
+            remaining -= area;
+        }
+        None
+    }
+
+    /// Like [`Self::time_for_requests`], but holds the final rate past the end of the profile, so
+    /// that a request budget is always reached when the run ends at a non-zero rate.
+    pub(crate) fn time_for_requests_held(&self, requests: f64) -> Option<Duration> {
+        if let Some(at) = self.time_for_requests(requests) {
+            return Some(at);
+        }
+        let &(end, rate) = self.points.last()?;
+        if rate <= 0.0_f64 {
+            return None;
+        }
+        let beyond = (requests - self.requests_until(end)) / rate;
+        Some(end.saturating_add(Duration::try_from_secs_f64(beyond).ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(s: u64) -> Duration {
+        Duration::from_secs(s)
+    }
+
+    fn assert_close(got: f64, expected: f64) {
+        assert!(
+            (got - expected).abs() < 1e-9,
+            "expected {expected}, got {got}"
+        );
+    }
+
+    #[test]
+    fn constant_profile_has_two_points() {
+        let profile = RateProfile::ramped(10.0, secs(5), Duration::ZERO, Duration::ZERO);
+        assert_eq!(profile.points(), &[(secs(0), 10.0), (secs(5), 10.0)]);
+    }
+
+    #[test]
+    fn constant_profile_integrates_to_rate_times_duration() {
+        let profile = RateProfile::ramped(10.0, secs(5), Duration::ZERO, Duration::ZERO);
+        assert_close(profile.requests_until(secs(0)), 0.0);
+        assert_close(profile.requests_until(secs(2)), 20.0);
+        assert_close(profile.requests_until(secs(5)), 50.0);
+        assert_close(profile.requests_until(secs(60)), 50.0);
+    }
+
+    #[test]
+    fn ramp_up_is_a_triangle() {
+        let profile = RateProfile::ramped(100.0, secs(30), secs(10), Duration::ZERO);
+        assert_eq!(profile.points()[0], (secs(0), 0.0));
+        assert_close(profile.requests_until(secs(5)), 125.0);
+        assert_close(profile.requests_until(secs(10)), 500.0);
+        assert_close(profile.requests_until(secs(30)), 2500.0);
+    }
+
+    #[test]
+    fn stepped_profile_holds_each_rate() {
+        let profile =
+            RateProfile::stepped([(secs(60), 100.0), (secs(300), 500.0), (secs(60), 0.0)]);
+        assert_eq!(
+            profile.points(),
+            &[
+                (secs(0), 100.0),
+                (secs(60), 100.0),
+                (secs(60), 500.0),
+                (secs(360), 500.0),
+                (secs(360), 0.0),
+                (secs(420), 0.0),
+            ]
+        );
+        assert_close(profile.requests_until(secs(60)), 6000.0);
+        assert_close(profile.requests_until(secs(61)), 6500.0);
+        assert_close(profile.requests_until(secs(420)), 156_000.0);
+    }
+
+    #[test]
+    fn time_for_requests_inverts_constant_rate() {
+        let profile = RateProfile::ramped(10.0, secs(5), Duration::ZERO, Duration::ZERO);
+        assert_eq!(profile.time_for_requests(0.0), Some(secs(0)));
+        assert_eq!(
+            profile.time_for_requests(25.0),
+            Some(Duration::from_millis(2500))
+        );
+        assert_eq!(profile.time_for_requests(50.0), Some(secs(5)));
+        assert_eq!(profile.time_for_requests(50.5), None);
+    }
+
+    #[test]
+    fn time_for_requests_inverts_ramps_and_steps() {
+        let ramp = RateProfile::ramped(100.0, secs(30), secs(10), secs(10));
+        for t in [0.0, 3.0, 10.0, 17.5, 25.0, 30.0] {
+            let requests = ramp.requests_until(Duration::from_secs_f64(t));
+            assert_close(ramp.time_for_requests(requests).unwrap().as_secs_f64(), t);
+        }
+        let stepped = RateProfile::stepped([(secs(10), 1.0), (secs(10), 0.0), (secs(10), 2.0)]);
+        assert_eq!(stepped.time_for_requests(10.0), Some(secs(10)));
+        assert_eq!(stepped.time_for_requests(12.0), Some(secs(21)));
+    }
+
+    #[test]
+    fn time_for_requests_held_extends_the_final_rate() {
+        let profile = RateProfile::ramped(10.0, secs(5), secs(2), Duration::ZERO);
+        assert_eq!(
+            profile.time_for_requests_held(20.0),
+            profile.time_for_requests(20.0)
+        );
+        assert_eq!(profile.time_for_requests_held(50.0), Some(secs(6)));
+        let ends_at_zero = RateProfile::ramped(10.0, secs(5), Duration::ZERO, secs(1));
+        assert_eq!(ends_at_zero.time_for_requests_held(100.0), None);
+    }
+
+    #[test]
+    fn ramp_down_ends_at_zero() {
+        let profile = RateProfile::ramped(100.0, secs(30), secs(10), secs(10));
+        assert_eq!(profile.points().last(), Some(&(secs(30), 0.0)));
+        assert_close(profile.requests_until(secs(20)), 1500.0);
+        assert_close(profile.requests_until(secs(30)), 2000.0);
+    }
+}
@@ -10,5 +10,11 @@
 //! config.encode(&mut buf)?;
 //! let decoded = LoadTestConfig::decode(&buf[..])?;
 //! ```
+#![expect(
+    clippy::doc_markdown,
+    clippy::trivially_copy_pass_by_ref,
+    clippy::pattern_type_mismatch,
+    reason = "prost-generated enum helpers (`as_str_name`, `from_str_name`)"
+)]
 
 include!(concat!(env!("OUT_DIR"), "/loadtest.rs"));
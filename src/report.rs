@@ -1,50 +1,52 @@
 //! Run report generation (CDF, protobuf encoding). Caller writes the returned bytes (e.g. with `tokio::fs::write`).
 
+use std::collections::BTreeMap;
 use std::time::{Duration, UNIX_EPOCH};
 
 use prost::Message as _;
 use rust_decimal::prelude::ToPrimitive as _;
 
 use crate::cdf;
-use crate::cli::Args;
+use crate::cli::{Args, ArrivalProcess};
+use crate::histogram::Histogram;
 use crate::proto::{
-    CdfPoint, Header, LoadTestConfig, LoadTestRunReport, WorkerStats as ProtoWorkerStats,
+    ArrivalProcess as ProtoArrivalProcess, AssertionResult, CdfPoint, Header,
+    LatencyPoint as ProtoLatencyPoint, LoadTestConfig, LoadTestRunReport, Probe as ProtoProbe,
+    RatePoint, RequestOutcome, RequestResult, RunStatus as ProtoRunStatus, SizePoint,
+    Stage as ProtoStage, TerminationMode, WorkerStats as ProtoWorkerStats,
 };
+use crate::search::Probe;
+use crate::stages::{Stage, StageTarget};
 use crate::stats::WorkerStats;
-use crate::worker_manager::RunResult;
+use crate::timeline::LatencyPoint;
+use crate::work_unit::{ExecutionResult, HttpRequestOutcome};
+use crate::worker_manager::{RunResult, RunStatus};
 
-const START_PERCENTILE: f64 = 1.0;
-const RESOLUTION: u64 = 16;
-const STEPS: u64 = 4;
+pub(crate) const START_PERCENTILE: f64 = 1.0;
+pub(crate) const RESOLUTION: u64 = 16;
+pub(crate) const STEPS: u64 = 4;
 
 /// Builds the run report and returns the serialized protobuf. Prints CDFs to stdout.
 /// Caller is responsible for writing the bytes (e.g. `tokio::fs::write(path, &bytes).await`).
 pub(crate) fn build_run_report(args: &Args, result: &RunResult) -> Vec<u8> {
-    let cdf_success = cdf::calculate_cdf(
-        START_PERCENTILE,
-        RESOLUTION,
-        STEPS,
-        result.success_latencies.iter(),
-    );
+    let cdf_success = latency_cdf(&result.success_latencies);
+    let cdf_non_success = latency_cdf(&result.non_success_latencies);
 
-    let cdf_non_success = cdf::calculate_cdf(
-        START_PERCENTILE,
-        RESOLUTION,
-        STEPS,
-        result.non_success_latencies.iter(),
-    );
+    let mut all_latencies = result.success_latencies.clone();
+    all_latencies.merge(&result.non_success_latencies);
+    let cdf_all = latency_cdf(&all_latencies);
 
-    let cdf_all = cdf::calculate_cdf(
-        START_PERCENTILE,
-        RESOLUTION,
-        STEPS,
-        result
-            .success_latencies
-            .iter()
-            .chain(result.non_success_latencies.iter()),
-    );
+    // Measured from the scheduled send time, so that client or server stalls show up in the tail.
+    let cdf_response_time = latency_cdf(&result.response_times);
 
-    let report = build_proto(args, result, cdf_success, cdf_non_success, cdf_all);
+    let report = build_proto(
+        args,
+        result,
+        cdf_success,
+        cdf_non_success,
+        cdf_all,
+        cdf_response_time,
+    );
 
     report.encode_to_vec()
 }
@@ -52,9 +54,10 @@ pub(crate) fn build_run_report(args: &Args, result: &RunResult) -> Vec<u8> {
 fn build_proto(
     args: &Args,
     result: &RunResult,
-    cdf_success: Vec<(f64, Duration)>,
-    cdf_non_success: Vec<(f64, Duration)>,
-    cdf_all: Vec<(f64, Duration)>,
+    cdf_success: Vec<CdfPoint>,
+    cdf_non_success: Vec<CdfPoint>,
+    cdf: Vec<CdfPoint>,
+    cdf_response_time: Vec<CdfPoint>,
 ) -> LoadTestRunReport {
     let run_timestamp_unix_nanos = system_time_to_unix_nanos(result.run_timestamp);
 
@@ -66,15 +69,25 @@ fn build_proto(
         .map(map_worker_stats_to_proto)
         .collect();
 
-    let cdf = cdf_all.into_iter().map(map_cdf_point_to_proto).collect();
-    let cdf_success = cdf_success
-        .into_iter()
-        .map(map_cdf_point_to_proto)
-        .collect();
-    let cdf_non_success = cdf_non_success
-        .into_iter()
-        .map(map_cdf_point_to_proto)
+    let target_rate = result
+        .target_rate
+        .iter()
+        .map(map_rate_point_to_proto)
         .collect();
+    let stages = args
+        .stages
+        .as_deref()
+        .map_or_else(Vec::new, map_stages_to_proto);
+    let probes = result.search.as_ref().map_or_else(Vec::new, |search| {
+        search.probes.iter().map(map_probe_to_proto).collect()
+    });
+    let capacity_requests_per_second = result
+        .search
+        .as_ref()
+        .and_then(|search| search.capacity)
+        .map_or(0.0_f64, |capacity| {
+            capacity.to_f64().expect("capacity fits in f64")
+        });
 
     LoadTestRunReport {
         run_timestamp_unix_nanos,
@@ -83,11 +96,57 @@ fn build_proto(
         cdf,
         cdf_success,
         cdf_non_success,
+        target_rate,
+        stages,
+        cdf_response_time,
+        status: map_run_status_to_proto(result.status).into(),
+        run_duration_nanos: duration_to_nanos(result.run_duration),
+        probes,
+        capacity_requests_per_second,
+        abort_reason: result
+            .abort
+            .as_ref()
+            .map_or_else(String::new, ToString::to_string),
+        status_codes: map_status_codes_to_proto(&result.status_codes),
+        cdf_dns: latency_cdf(&result.phase_times.dns),
+        cdf_connect: latency_cdf(&result.phase_times.connect),
+        cdf_tls: latency_cdf(&result.phase_times.tls),
+        cdf_ttfb: latency_cdf(&result.phase_times.ttfb),
+        cdf_body: latency_cdf(&result.phase_times.body),
+        // Time to the response headers and to the end of the body, so that streaming or large
+        // responses can be told apart from slow ones.
+        cdf_time_to_headers: latency_cdf(&result.time_to_headers),
+        cdf_full_response: latency_cdf(&result.full_response),
+        request_bytes: result.request_bytes,
+        response_bytes: result.response_bytes,
+        cdf_response_size: cdf::calculate_cdf(
+            START_PERCENTILE,
+            RESOLUTION,
+            STEPS,
+            &result.response_sizes,
+        )
+        .into_iter()
+        .map(|(percentile, bytes)| SizePoint { percentile, bytes })
+        .collect(),
+        assertions: map_assertions_to_proto(args, &result.assertion_failures),
+        failure_samples: result
+            .failure_samples
+            .iter()
+            .map(map_failure_to_proto)
+            .collect(),
+        latency_over_time: result
+            .latency_over_time
+            .iter()
+            .map(map_latency_point_to_proto)
+            .collect(),
+        dropped_events: result.dropped_events,
+        dropped_tickets: result.dropped_tickets,
+        dropped_feedback: result.dropped_feedback,
     }
 }
 
 /// Converts a `SystemTime` to Unix timestamp in nanoseconds. Panics if before epoch or overflow.
-fn system_time_to_unix_nanos(t: std::time::SystemTime) -> i64 {
+pub(crate) fn system_time_to_unix_nanos(t: std::time::SystemTime) -> i64 {
     t.duration_since(UNIX_EPOCH)
         .expect("run timestamp is before UNIX epoch")
         .as_nanos()
@@ -102,6 +161,22 @@ fn args_to_proto(args: &Args) -> LoadTestConfig {
     let duration_secs =
         i64::try_from(args.duration.as_secs()).expect("duration seconds fit in i64");
     let headers = args.header.iter().map(map_header_to_proto).collect();
+    let concurrency = args
+        .concurrency
+        .map_or(0, |c| u32::try_from(c).expect("concurrency fits in u32"));
+    let think_time_nanos = args.think_time.map_or(0, duration_to_nanos);
+    let ramp_up_nanos = args.ramp_up.map_or(0, duration_to_nanos);
+    let ramp_down_nanos = args.ramp_down.map_or(0, duration_to_nanos);
+    let arrival = map_arrival_to_proto(args.arrival).into();
+    let workers = args
+        .workers
+        .map_or(0, |n| u32::try_from(n).expect("worker count fits in u32"));
+    let threads = args
+        .threads
+        .map_or(0, |n| u32::try_from(n).expect("thread count fits in u32"));
+    let max_in_flight = args
+        .max_in_flight
+        .map_or(0, |n| u32::try_from(n).expect("max in flight fits in u32"));
 
     LoadTestConfig {
         url,
@@ -109,6 +184,67 @@ fn args_to_proto(args: &Args) -> LoadTestConfig {
         requests_per_second,
         duration_secs,
         headers,
+        concurrency,
+        think_time_nanos,
+        ramp_up_nanos,
+        ramp_down_nanos,
+        arrival,
+        seed: args.seed,
+        max_in_flight,
+        workers,
+        threads,
+        warmup_nanos: args.warmup.map_or(0, duration_to_nanos),
+        drain_timeout_nanos: args.drain_timeout.map_or(0, duration_to_nanos),
+        termination: if args.requests.is_some() {
+            TerminationMode::Requests
+        } else {
+            TerminationMode::Duration
+        }
+        .into(),
+        requests: args.requests.unwrap_or(0),
+        search: args.search.is_some(),
+        slo_p99_nanos: args
+            .search
+            .and_then(|search| search.slo_p99)
+            .map_or(0, duration_to_nanos),
+        slo_error_rate: args
+            .search
+            .and_then(|search| search.slo_error_rate)
+            .unwrap_or(0.0_f64),
+        search_tolerance: args.search.map_or(0.0_f64, |search| search.tolerance),
+        target_latency_nanos: args
+            .adaptive
+            .map_or(0, |adaptive| duration_to_nanos(adaptive.target_latency)),
+        target_percentile: args
+            .adaptive
+            .map_or(0.0_f64, |adaptive| adaptive.percentile),
+        adapt_interval_nanos: args
+            .adaptive
+            .map_or(0, |adaptive| duration_to_nanos(adaptive.interval)),
+        abort: args.abort.is_some(),
+        abort_error_rate: args.abort.map_or(0.0_f64, |abort| abort.error_rate),
+        abort_window_nanos: args
+            .abort
+            .map_or(0, |abort| duration_to_nanos(abort.window)),
+        assertions: args.assertions.iter().map(ToString::to_string).collect(),
+        histogram_precision: u32::from(args.histogram_precision),
+        snapshot_interval_nanos: duration_to_nanos(args.snapshot_interval),
+    }
+}
+
+fn map_arrival_to_proto(arrival: ArrivalProcess) -> ProtoArrivalProcess {
+    match arrival {
+        ArrivalProcess::Constant => ProtoArrivalProcess::Constant,
+        ArrivalProcess::Poisson => ProtoArrivalProcess::Poisson,
+        ArrivalProcess::Uniform => ProtoArrivalProcess::Uniform,
+    }
+}
+
+fn map_run_status_to_proto(status: RunStatus) -> ProtoRunStatus {
+    match status {
+        RunStatus::Completed => ProtoRunStatus::Completed,
+        RunStatus::Interrupted => ProtoRunStatus::Interrupted,
+        RunStatus::Aborted => ProtoRunStatus::Aborted,
     }
 }
 
@@ -125,7 +261,7 @@ fn map_header_to_proto((name, value): (&http::HeaderName, &http::HeaderValue)) -
 fn map_worker_stats_to_proto(w: &WorkerStats) -> ProtoWorkerStats {
     let timestamp_unix_nanos = system_time_to_unix_nanos(w.timestamp);
     let worker_id = u32::try_from(w.id).expect("worker id fits in u32");
-    let elapsed_nanos = w.elapsed.as_nanos().try_into().unwrap_or(u64::MAX);
+    let elapsed_nanos = duration_to_nanos(w.elapsed);
     let request_sent = u64::try_from(w.request_sent).expect("request_sent fits in u64");
     let in_flight = u64::try_from(w.in_flight).expect("in_flight fits in u64");
     let informational_response = w.informational_response;
@@ -135,6 +271,18 @@ fn map_worker_stats_to_proto(w: &WorkerStats) -> ProtoWorkerStats {
     let server_error_response = w.server_error_response;
     let other_error_response = w.other_error_response;
     let timeouts = w.timeouts;
+    let connect_errors = w.connect_errors;
+    let dns_errors = w.dns_errors;
+    let tls_errors = w.tls_errors;
+    let connection_resets = w.connection_resets;
+    let body_errors = w.body_errors;
+    let assertion_failed = w.assertion_failed;
+    let dropped = w.dropped;
+    let abandoned = w.abandoned;
+    let warmup = w.warmup;
+    let status_codes = map_status_codes_to_proto(&w.status_codes);
+    let request_bytes = w.request_bytes;
+    let response_bytes = w.response_bytes;
 
     ProtoWorkerStats {
         timestamp_unix_nanos,
@@ -149,33 +297,212 @@ fn map_worker_stats_to_proto(w: &WorkerStats) -> ProtoWorkerStats {
         server_error_response,
         other_error_response,
         timeouts,
+        dropped,
+        warmup,
+        abandoned,
+        connect_errors,
+        dns_errors,
+        tls_errors,
+        connection_resets,
+        body_errors,
+        status_codes,
+        request_bytes,
+        response_bytes,
+        assertion_failed,
+    }
+}
+
+fn map_status_codes_to_proto(status_codes: &BTreeMap<u16, u64>) -> BTreeMap<u32, u64> {
+    status_codes
+        .iter()
+        .map(|(&status_code, &count)| (u32::from(status_code), count))
+        .collect()
+}
+
+/// Each configured assertion with its failure count.
+fn map_assertions_to_proto(args: &Args, failures: &BTreeMap<usize, u64>) -> Vec<AssertionResult> {
+    args.assertions
+        .iter()
+        .enumerate()
+        .map(|(index, assertion)| AssertionResult {
+            assertion: assertion.to_string(),
+            failures: failures.get(&index).copied().unwrap_or(0),
+        })
+        .collect()
+}
+
+/// The parts of a request record that need no clock: latency, status, outcome and byte counts.
+pub(crate) fn map_request_to_proto(result: &ExecutionResult) -> RequestResult {
+    RequestResult {
+        latency_nanos: i64::try_from(result.duration.as_nanos()).unwrap_or(i64::MAX),
+        status_code: result.status_code.map_or(0, u32::from),
+        success: result.outcome == HttpRequestOutcome::SuccessResponse,
+        outcome: map_outcome_to_proto(result.outcome).into(),
+        request_bytes: result.request_bytes,
+        response_bytes: result.response_bytes,
+        ..RequestResult::default()
+    }
+}
+
+fn map_outcome_to_proto(outcome: HttpRequestOutcome) -> RequestOutcome {
+    match outcome {
+        HttpRequestOutcome::SuccessResponse => RequestOutcome::Success,
+        HttpRequestOutcome::InformationalResponse => RequestOutcome::Informational,
+        HttpRequestOutcome::RedirectionMessage => RequestOutcome::Redirection,
+        HttpRequestOutcome::ClientErrorResponse => RequestOutcome::ClientError,
+        HttpRequestOutcome::ServerErrorResponse => RequestOutcome::ServerError,
+        HttpRequestOutcome::OtherError => RequestOutcome::OtherError,
+        HttpRequestOutcome::Timeout => RequestOutcome::Timeout,
+        HttpRequestOutcome::ConnectError => RequestOutcome::ConnectError,
+        HttpRequestOutcome::DnsError => RequestOutcome::DnsError,
+        HttpRequestOutcome::TlsError => RequestOutcome::TlsError,
+        HttpRequestOutcome::ConnectionReset => RequestOutcome::ConnectionReset,
+        HttpRequestOutcome::BodyError => RequestOutcome::BodyError,
+        HttpRequestOutcome::AssertionFailed => RequestOutcome::AssertionFailed,
+    }
+}
+
+fn map_failure_to_proto(result: &ExecutionResult) -> RequestResult {
+    let mut sample = map_request_to_proto(result);
+    if let Some(ref failure) = result.failure {
+        sample.error_message = failure.error.clone().unwrap_or_default();
+        sample.timestamp_unix_nanos = system_time_to_unix_nanos(failure.at);
+        // Unlike the configured headers, response headers need not be valid UTF-8.
+        sample.headers = failure
+            .headers
+            .iter()
+            .map(|(name, value)| Header {
+                name: name.as_str().to_owned(),
+                value: String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            })
+            .collect();
+        sample.body.clone_from(&failure.body);
+        sample.body_truncated = failure.body_truncated;
+    }
+    sample
+}
+
+fn map_probe_to_proto(probe: &Probe) -> ProtoProbe {
+    ProtoProbe {
+        requests_per_second: probe
+            .requests_per_second
+            .to_f64()
+            .expect("probe rate fits in f64"),
+        p99_response_time_nanos: probe.p99.map_or(0, duration_to_nanos),
+        error_rate: probe.error_rate,
+        requests: probe.requests,
+        passed: probe.passed,
+        status: map_run_status_to_proto(probe.status).into(),
+    }
+}
+
+fn map_stages_to_proto(stages: &[Stage]) -> Vec<ProtoStage> {
+    let mut start = Duration::ZERO;
+    stages
+        .iter()
+        .map(|stage| {
+            let (requests_per_second, users) = match stage.target {
+                StageTarget::Rate(rps) => (rps.to_f64().unwrap_or(0.0_f64), 0),
+                StageTarget::Users(users) => (
+                    0.0_f64,
+                    u32::try_from(users).expect("stage users fit in u32"),
+                ),
+            };
+            let proto = ProtoStage {
+                start_nanos: duration_to_nanos(start),
+                duration_nanos: duration_to_nanos(stage.duration),
+                requests_per_second,
+                users,
+            };
+            start = start.saturating_add(stage.duration);
+            proto
+        })
+        .collect()
+}
+
+fn map_rate_point_to_proto(&(elapsed, rps): &(Duration, f64)) -> RatePoint {
+    RatePoint {
+        elapsed_nanos: duration_to_nanos(elapsed),
+        requests_per_second: rps,
+    }
+}
+
+fn map_latency_point_to_proto(point: &LatencyPoint) -> ProtoLatencyPoint {
+    ProtoLatencyPoint {
+        elapsed_nanos: duration_to_nanos(point.elapsed),
+        requests: point.requests,
+        p50_nanos: duration_to_nanos(point.p50),
+        p90_nanos: duration_to_nanos(point.p90),
+        p99_nanos: duration_to_nanos(point.p99),
+        max_nanos: duration_to_nanos(point.max),
     }
 }
 
-fn map_cdf_point_to_proto((p, d): (f64, Duration)) -> CdfPoint {
+/// Converts a `Duration` to nanoseconds, saturating at `u64::MAX`.
+fn duration_to_nanos(d: Duration) -> u64 {
+    d.as_nanos().try_into().unwrap_or(u64::MAX)
+}
+
+/// CDF of `latencies` (nanoseconds), in proto form.
+fn latency_cdf(latencies: &Histogram) -> Vec<CdfPoint> {
+    cdf::calculate_cdf(START_PERCENTILE, RESOLUTION, STEPS, latencies)
+        .into_iter()
+        .map(map_cdf_point_to_proto)
+        .collect()
+}
+
+fn map_cdf_point_to_proto((p, nanos): (f64, u64)) -> CdfPoint {
     CdfPoint {
         percentile: p,
-        latency_nanos: i64::try_from(d.as_nanos()).unwrap_or(i64::MAX),
+        latency_nanos: i64::try_from(nanos).unwrap_or(i64::MAX),
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::path::PathBuf;
     use std::str::FromStr as _;
     use std::time::Duration;
 
     use prost::Message as _;
 
-    use crate::cli::{Args, HttpProtocol};
-    use crate::proto::LoadTestRunReport;
+    use crate::abort::{AbortPolicy, AbortReason};
+    use crate::adaptive::Adaptive;
+    use crate::assertion::Assertion;
+    use crate::cli::{Args, ArrivalProcess, HttpProtocol};
+    use crate::histogram::Histogram;
+    use crate::proto::{
+        CdfPoint, LatencyPoint as ProtoLatencyPoint, LoadTestRunReport,
+        RunStatus as ProtoRunStatus, TerminationMode,
+    };
     use crate::report::build_run_report;
     use crate::report_fixtures::{
         expected_cdf, expected_empty, expected_epoch, expected_full, expected_multi_worker,
         expected_no_headers, expected_zero_rps,
     };
-    use crate::stats::WorkerStats;
-    use crate::worker_manager::RunResult;
+    use crate::search::{Probe, Search, SearchResult};
+    use crate::stages::{Stage, StageTarget};
+    use crate::stats::{PhaseTimes, WorkerStats};
+    use crate::timeline::LatencyPoint;
+    use crate::work_unit::{ExecutionResult, FailureDetail, HttpRequestOutcome, Phases};
+    use crate::worker_manager::{RunResult, RunStatus};
+
+    fn latencies(latencies: &[Duration]) -> Histogram {
+        let mut histogram = Histogram::default();
+        for &latency in latencies {
+            histogram.record_duration(latency);
+        }
+        histogram
+    }
+
+    fn sizes(sizes: &[u64]) -> Histogram {
+        let mut histogram = Histogram::default();
+        for &size in sizes {
+            histogram.record(size);
+        }
+        histogram
+    }
 
     fn minimal_args(url: &str, method: &str, rps: u32, duration_secs: u64) -> Args {
         let mut header = http::HeaderMap::new();
@@ -200,10 +527,31 @@ mod tests {
             key: None,
             location: false,
             requests_per_second: rust_decimal::Decimal::from(rps),
+            concurrency: None,
+            think_time: None,
+            ramp_up: None,
+            ramp_down: None,
+            stages: None,
+            arrival: ArrivalProcess::Constant,
+            seed: 0,
+            max_in_flight: None,
+            workers: None,
+            threads: None,
+            warmup: None,
+            requests: None,
+            search: None,
+            adaptive: None,
+            abort: None,
             duration: Duration::from_secs(duration_secs),
+            drain_timeout: None,
+            assertions: Vec::new(),
+            expect_body_max_bytes: 1_048_576,
             max_time: None,
             connect_timeout: None,
             output: PathBuf::from("report.pb"),
+            events: None,
+            histogram_precision: 3,
+            snapshot_interval: Duration::from_millis(250),
             protocol: HttpProtocol::Http1_1,
             payload: None,
             identity: None,
@@ -218,10 +566,12 @@ mod tests {
         let run_timestamp = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
         RunResult {
             run_timestamp,
+            status: RunStatus::Completed,
+            run_duration: Duration::ZERO,
             worker_stats: vec![WorkerStats {
                 timestamp: run_timestamp,
                 id: 0,
-                elapsed: Duration::from_millis(5000),
+                elapsed: Duration::from_secs(5),
                 request_sent: 100,
                 in_flight: 0,
                 informational_response: 0,
@@ -231,13 +581,42 @@ mod tests {
                 server_error_response: 1,
                 other_error_response: 2,
                 timeouts: 0,
+                connect_errors: 0,
+                dns_errors: 0,
+                tls_errors: 0,
+                connection_resets: 0,
+                body_errors: 0,
+                assertion_failed: 0,
+                status_codes: BTreeMap::new(),
+                request_bytes: 0,
+                response_bytes: 0,
+                dropped: 0,
+                abandoned: 0,
+                warmup: false,
             }],
-            success_latencies: vec![
+            success_latencies: latencies(&[
                 Duration::from_millis(10),
                 Duration::from_millis(20),
                 Duration::from_millis(30),
-            ],
-            non_success_latencies: vec![Duration::from_millis(100)],
+            ]),
+            non_success_latencies: latencies(&[Duration::from_millis(100)]),
+            response_times: Histogram::default(),
+            target_rate: vec![],
+            status_codes: BTreeMap::new(),
+            assertion_failures: BTreeMap::new(),
+            time_to_headers: Histogram::default(),
+            full_response: Histogram::default(),
+            request_bytes: 0,
+            response_bytes: 0,
+            response_sizes: Histogram::default(),
+            phase_times: PhaseTimes::default(),
+            failure_samples: vec![],
+            latency_over_time: vec![],
+            dropped_events: 0,
+            dropped_tickets: 0,
+            dropped_feedback: 0,
+            search: None,
+            abort: None,
         }
     }
 
@@ -245,9 +624,28 @@ mod tests {
         use std::time::UNIX_EPOCH;
         RunResult {
             run_timestamp: UNIX_EPOCH + Duration::from_secs(1000),
+            status: RunStatus::Completed,
+            run_duration: Duration::ZERO,
             worker_stats: vec![],
-            success_latencies: vec![],
-            non_success_latencies: vec![],
+            success_latencies: Histogram::default(),
+            non_success_latencies: Histogram::default(),
+            response_times: Histogram::default(),
+            target_rate: vec![],
+            status_codes: BTreeMap::new(),
+            assertion_failures: BTreeMap::new(),
+            time_to_headers: Histogram::default(),
+            full_response: Histogram::default(),
+            request_bytes: 0,
+            response_bytes: 0,
+            response_sizes: Histogram::default(),
+            phase_times: PhaseTimes::default(),
+            failure_samples: vec![],
+            latency_over_time: vec![],
+            dropped_events: 0,
+            dropped_tickets: 0,
+            dropped_feedback: 0,
+            search: None,
+            abort: None,
         }
     }
 
@@ -256,11 +654,13 @@ mod tests {
         let run_timestamp = UNIX_EPOCH + Duration::from_secs(2000);
         RunResult {
             run_timestamp,
+            status: RunStatus::Completed,
+            run_duration: Duration::ZERO,
             worker_stats: vec![
                 WorkerStats {
                     timestamp: run_timestamp,
                     id: 0,
-                    elapsed: Duration::from_secs(60),
+                    elapsed: Duration::from_mins(1),
                     request_sent: 100,
                     in_flight: 0,
                     informational_response: 0,
@@ -270,11 +670,23 @@ mod tests {
                     server_error_response: 5,
                     other_error_response: 5,
                     timeouts: 0,
+                    connect_errors: 0,
+                    dns_errors: 0,
+                    tls_errors: 0,
+                    connection_resets: 0,
+                    body_errors: 0,
+                    assertion_failed: 0,
+                    status_codes: BTreeMap::new(),
+                    request_bytes: 0,
+                    response_bytes: 0,
+                    dropped: 0,
+                    abandoned: 0,
+                    warmup: false,
                 },
                 WorkerStats {
                     timestamp: run_timestamp,
                     id: 1,
-                    elapsed: Duration::from_secs(60),
+                    elapsed: Duration::from_mins(1),
                     request_sent: 120,
                     in_flight: 2,
                     informational_response: 0,
@@ -284,10 +696,39 @@ mod tests {
                     server_error_response: 2,
                     other_error_response: 3,
                     timeouts: 0,
+                    connect_errors: 0,
+                    dns_errors: 0,
+                    tls_errors: 0,
+                    connection_resets: 0,
+                    body_errors: 0,
+                    assertion_failed: 0,
+                    status_codes: BTreeMap::new(),
+                    request_bytes: 0,
+                    response_bytes: 0,
+                    dropped: 0,
+                    abandoned: 0,
+                    warmup: false,
                 },
             ],
-            success_latencies: vec![Duration::from_millis(50)],
-            non_success_latencies: vec![],
+            success_latencies: latencies(&[Duration::from_millis(50)]),
+            non_success_latencies: Histogram::default(),
+            response_times: Histogram::default(),
+            target_rate: vec![],
+            status_codes: BTreeMap::new(),
+            assertion_failures: BTreeMap::new(),
+            time_to_headers: Histogram::default(),
+            full_response: Histogram::default(),
+            request_bytes: 0,
+            response_bytes: 0,
+            response_sizes: Histogram::default(),
+            phase_times: PhaseTimes::default(),
+            failure_samples: vec![],
+            latency_over_time: vec![],
+            dropped_events: 0,
+            dropped_tickets: 0,
+            dropped_feedback: 0,
+            search: None,
+            abort: None,
         }
     }
 
@@ -295,13 +736,32 @@ mod tests {
         use std::time::UNIX_EPOCH;
         RunResult {
             run_timestamp: UNIX_EPOCH + Duration::from_secs(0),
+            status: RunStatus::Completed,
+            run_duration: Duration::ZERO,
             worker_stats: vec![],
-            success_latencies: vec![
+            success_latencies: latencies(&[
                 Duration::from_millis(10),
                 Duration::from_millis(20),
                 Duration::from_millis(30),
-            ],
-            non_success_latencies: vec![Duration::from_millis(100)],
+            ]),
+            non_success_latencies: latencies(&[Duration::from_millis(100)]),
+            response_times: Histogram::default(),
+            target_rate: vec![],
+            status_codes: BTreeMap::new(),
+            assertion_failures: BTreeMap::new(),
+            time_to_headers: Histogram::default(),
+            full_response: Histogram::default(),
+            request_bytes: 0,
+            response_bytes: 0,
+            response_sizes: Histogram::default(),
+            phase_times: PhaseTimes::default(),
+            failure_samples: vec![],
+            latency_over_time: vec![],
+            dropped_events: 0,
+            dropped_tickets: 0,
+            dropped_feedback: 0,
+            search: None,
+            abort: None,
         }
     }
 
@@ -309,9 +769,28 @@ mod tests {
         use std::time::UNIX_EPOCH;
         RunResult {
             run_timestamp: UNIX_EPOCH + Duration::from_secs(1),
+            status: RunStatus::Completed,
+            run_duration: Duration::ZERO,
             worker_stats: vec![],
-            success_latencies: vec![Duration::from_millis(1)],
-            non_success_latencies: vec![],
+            success_latencies: latencies(&[Duration::from_millis(1)]),
+            non_success_latencies: Histogram::default(),
+            response_times: Histogram::default(),
+            target_rate: vec![],
+            status_codes: BTreeMap::new(),
+            assertion_failures: BTreeMap::new(),
+            time_to_headers: Histogram::default(),
+            full_response: Histogram::default(),
+            request_bytes: 0,
+            response_bytes: 0,
+            response_sizes: Histogram::default(),
+            phase_times: PhaseTimes::default(),
+            failure_samples: vec![],
+            latency_over_time: vec![],
+            dropped_events: 0,
+            dropped_tickets: 0,
+            dropped_feedback: 0,
+            search: None,
+            abort: None,
         }
     }
 
@@ -319,9 +798,28 @@ mod tests {
         use std::time::UNIX_EPOCH;
         RunResult {
             run_timestamp: UNIX_EPOCH,
+            status: RunStatus::Completed,
+            run_duration: Duration::ZERO,
             worker_stats: vec![],
-            success_latencies: vec![Duration::from_nanos(1)],
-            non_success_latencies: vec![],
+            success_latencies: latencies(&[Duration::from_nanos(1)]),
+            non_success_latencies: Histogram::default(),
+            response_times: Histogram::default(),
+            target_rate: vec![],
+            status_codes: BTreeMap::new(),
+            assertion_failures: BTreeMap::new(),
+            time_to_headers: Histogram::default(),
+            full_response: Histogram::default(),
+            request_bytes: 0,
+            response_bytes: 0,
+            response_sizes: Histogram::default(),
+            phase_times: PhaseTimes::default(),
+            failure_samples: vec![],
+            latency_over_time: vec![],
+            dropped_events: 0,
+            dropped_tickets: 0,
+            dropped_feedback: 0,
+            search: None,
+            abort: None,
         }
     }
 
@@ -329,9 +827,28 @@ mod tests {
         use std::time::UNIX_EPOCH;
         RunResult {
             run_timestamp: UNIX_EPOCH + Duration::from_secs(1),
+            status: RunStatus::Completed,
+            run_duration: Duration::ZERO,
             worker_stats: vec![],
-            success_latencies: vec![Duration::from_millis(1)],
-            non_success_latencies: vec![],
+            success_latencies: latencies(&[Duration::from_millis(1)]),
+            non_success_latencies: Histogram::default(),
+            response_times: Histogram::default(),
+            target_rate: vec![],
+            status_codes: BTreeMap::new(),
+            assertion_failures: BTreeMap::new(),
+            time_to_headers: Histogram::default(),
+            full_response: Histogram::default(),
+            request_bytes: 0,
+            response_bytes: 0,
+            response_sizes: Histogram::default(),
+            phase_times: PhaseTimes::default(),
+            failure_samples: vec![],
+            latency_over_time: vec![],
+            dropped_events: 0,
+            dropped_tickets: 0,
+            dropped_feedback: 0,
+            search: None,
+            abort: None,
         }
     }
 
@@ -403,4 +920,524 @@ mod tests {
         let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
         assert_eq!(report, expected_zero_rps());
     }
+
+    #[test]
+    fn build_run_report_target_rate_and_ramps() {
+        let mut args = minimal_args("https://ramp.example/", "GET", 100, 60);
+        args.ramp_up = Some(Duration::from_secs(10));
+        args.ramp_down = Some(Duration::from_secs(5));
+        let mut result = run_result_empty();
+        result.target_rate = vec![
+            (Duration::ZERO, 0.0),
+            (Duration::from_secs(10), 100.0),
+            (Duration::from_secs(55), 100.0),
+            (Duration::from_mins(1), 0.0),
+        ];
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        let config = report.config.unwrap();
+        assert_eq!(config.ramp_up_nanos, 10_000_000_000);
+        assert_eq!(config.ramp_down_nanos, 5_000_000_000);
+        let points: Vec<(u64, f64)> = report
+            .target_rate
+            .iter()
+            .map(|p| (p.elapsed_nanos, p.requests_per_second))
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                (0, 0.0),
+                (10_000_000_000, 100.0),
+                (55_000_000_000, 100.0),
+                (60_000_000_000, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn build_run_report_response_time_cdf_is_separate_from_service_time() {
+        let args = minimal_args("https://co.example/", "GET", 10, 1);
+        let mut result = run_result_empty();
+        result.success_latencies = latencies(&[Duration::from_millis(10)]);
+        result.response_times = latencies(&[Duration::from_millis(250)]);
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        let max = |cdf: &[CdfPoint]| cdf.iter().map(|p| p.latency_nanos).max().unwrap();
+        assert_eq!(max(&report.cdf), 10_000_000);
+        assert_eq!(max(&report.cdf_response_time), 250_000_000);
+    }
+
+    #[test]
+    fn build_run_report_stage_boundaries() {
+        let mut args = minimal_args("https://stages.example/", "GET", 1, 1);
+        args.stages = Some(vec![
+            Stage {
+                duration: Duration::from_mins(1),
+                target: StageTarget::Rate(rust_decimal::Decimal::from(100)),
+            },
+            Stage {
+                duration: Duration::from_mins(5),
+                target: StageTarget::Rate(rust_decimal::Decimal::new(25, 1)),
+            },
+        ]);
+        let result = run_result_empty();
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        let stages: Vec<(u64, u64, f64, u32)> = report
+            .stages
+            .iter()
+            .map(|s| {
+                (
+                    s.start_nanos,
+                    s.duration_nanos,
+                    s.requests_per_second,
+                    s.users,
+                )
+            })
+            .collect();
+        assert_eq!(
+            stages,
+            vec![
+                (0, 60_000_000_000, 100.0, 0),
+                (60_000_000_000, 300_000_000_000, 2.5, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn build_run_report_config_request_budget() {
+        let mut args = minimal_args("https://budget.example/", "GET", 20, 0);
+        args.requests = Some(100);
+        args.duration = Duration::from_secs(5);
+        let bytes = build_run_report(&args, &run_result_empty());
+        let config = LoadTestRunReport::decode(bytes.as_slice())
+            .unwrap()
+            .config
+            .unwrap();
+        assert_eq!(config.termination(), TerminationMode::Requests);
+        assert_eq!(config.requests, 100);
+        assert_eq!(config.duration_secs, 5);
+    }
+
+    #[test]
+    fn build_run_report_search_probes_and_capacity() {
+        let mut args = minimal_args("https://search.example/", "GET", 100, 10);
+        args.search = Some(Search {
+            slo_p99: Some(Duration::from_millis(50)),
+            slo_error_rate: None,
+            tolerance: 0.05,
+        });
+        let mut result = run_result_empty();
+        let probe = |rps: i64, passed: bool| Probe {
+            requests_per_second: rust_decimal::Decimal::from(rps),
+            p99: Some(Duration::from_millis(if passed { 20 } else { 80 })),
+            error_rate: 0.0,
+            requests: 1000,
+            passed,
+            status: RunStatus::Completed,
+        };
+        let interrupted = Probe {
+            requests_per_second: rust_decimal::Decimal::from(175),
+            p99: None,
+            error_rate: 0.0,
+            requests: 0,
+            passed: false,
+            status: RunStatus::Interrupted,
+        };
+        result.search = Some(SearchResult {
+            probes: vec![
+                probe(100, true),
+                probe(200, false),
+                probe(150, true),
+                interrupted,
+            ],
+            capacity: Some(rust_decimal::Decimal::from(150)),
+        });
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        let config = report.config.unwrap();
+        assert!(config.search);
+        assert_eq!(config.slo_p99_nanos, 50_000_000);
+        let probes: Vec<(f64, u64, bool)> = report
+            .probes
+            .iter()
+            .map(|p| (p.requests_per_second, p.p99_response_time_nanos, p.passed))
+            .collect();
+        assert_eq!(
+            probes,
+            vec![
+                (100.0, 20_000_000, true),
+                (200.0, 80_000_000, false),
+                (150.0, 20_000_000, true),
+                (175.0, 0, false),
+            ]
+        );
+        assert_eq!(
+            report.probes.last().unwrap().status(),
+            ProtoRunStatus::Interrupted
+        );
+        assert_eq!(report.capacity_requests_per_second, 150.0);
+    }
+
+    #[test]
+    fn build_run_report_adaptive_settings_and_chosen_rate() {
+        let mut args = minimal_args("https://adaptive.example/", "GET", 100, 10);
+        args.adaptive = Some(Adaptive {
+            target_latency: Duration::from_millis(250),
+            percentile: 0.95,
+            interval: Duration::from_millis(500),
+        });
+        let mut result = run_result_empty();
+        result.target_rate = vec![
+            (Duration::ZERO, 100.0),
+            (Duration::from_millis(500), 100.0),
+            (Duration::from_millis(500), 50.0),
+        ];
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        let config = report.config.unwrap();
+        assert_eq!(config.target_latency_nanos, 250_000_000);
+        assert_eq!(config.target_percentile, 0.95);
+        assert_eq!(config.adapt_interval_nanos, 500_000_000);
+        let rates: Vec<f64> = report
+            .target_rate
+            .iter()
+            .map(|point| point.requests_per_second)
+            .collect();
+        assert_eq!(rates, vec![100.0, 100.0, 50.0]);
+    }
+
+    #[test]
+    fn build_run_report_config_closed_loop() {
+        let mut args = minimal_args("https://users.example/", "GET", 1, 30);
+        args.concurrency = Some(50);
+        args.think_time = Some(Duration::from_millis(200));
+        let result = run_result_empty();
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        let config = report.config.unwrap();
+        assert_eq!(config.concurrency, 50);
+        assert_eq!(config.think_time_nanos, 200_000_000);
+    }
+
+    #[test]
+    fn build_run_report_interrupted_run() {
+        let args = minimal_args("https://interrupted.example/", "GET", 10, 60);
+        let mut result = run_result_empty();
+        result.status = RunStatus::Interrupted;
+        result.run_duration = Duration::from_millis(12_500);
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        assert_eq!(report.status(), ProtoRunStatus::Interrupted);
+        assert_eq!(report.run_duration_nanos, 12_500_000_000);
+        assert_eq!(report.config.unwrap().duration_secs, 60);
+    }
+
+    #[test]
+    fn build_run_report_status_codes() {
+        let args = minimal_args("https://codes.example/", "GET", 10, 5);
+        let mut result = run_result_full();
+        result.status_codes = BTreeMap::from([(200, 95), (404, 2), (429, 3)]);
+        result.worker_stats[0].status_codes = BTreeMap::from([(200, 95), (429, 3)]);
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        assert_eq!(
+            report.status_codes,
+            BTreeMap::from([(200, 95), (404, 2), (429, 3)])
+        );
+        assert_eq!(
+            report.worker_stats[0].status_codes,
+            BTreeMap::from([(200, 95), (429, 3)])
+        );
+    }
+
+    #[test]
+    fn build_run_report_time_to_headers_and_full_response_cdfs() {
+        let args = minimal_args("https://stream.example/", "GET", 10, 5);
+        let mut result = run_result_empty();
+        result.time_to_headers = latencies(&[Duration::from_millis(5), Duration::from_millis(8)]);
+        result.full_response = latencies(&[Duration::from_millis(900)]);
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        let max = |cdf: &[CdfPoint]| cdf.iter().map(|p| p.latency_nanos).max().unwrap();
+        assert_eq!(max(&report.cdf_time_to_headers), 8_000_000);
+        assert_eq!(max(&report.cdf_full_response), 900_000_000);
+    }
+
+    #[test]
+    fn build_run_report_bytes_and_response_sizes() {
+        let args = minimal_args("https://download.example/", "GET", 10, 5);
+        let mut result = run_result_full();
+        result.request_bytes = 0;
+        result.response_bytes = 3_000_000;
+        result.response_sizes = sizes(&[1_000_000, 1_000_000, 500_000, 500_000]);
+        result.worker_stats[0].response_bytes = 3_000_000;
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        assert_eq!(report.response_bytes, 3_000_000);
+        assert_eq!(report.worker_stats[0].response_bytes, 3_000_000);
+        let sizes = &report.cdf_response_size;
+        assert_eq!(sizes.first().unwrap().bytes, 500_000);
+        assert_eq!(sizes.last().unwrap().bytes, 1_000_000);
+    }
+
+    #[test]
+    fn build_run_report_failure_samples() {
+        let args = minimal_args("https://failures.example/", "GET", 10, 5);
+        let mut result = run_result_empty();
+        let now = std::time::Instant::now();
+        let mut headers = http::HeaderMap::new();
+        headers.insert("retry-after", http::HeaderValue::from_static("5"));
+        headers.insert("x-raw", http::HeaderValue::from_bytes(b"\xff").unwrap());
+        result.failure_samples = vec![ExecutionResult {
+            outcome: HttpRequestOutcome::ServerErrorResponse,
+            status_code: Some(503),
+            duration: Duration::from_millis(12),
+            time_to_headers: None,
+            scheduled: now,
+            started: now,
+            phases: Phases::default(),
+            request_bytes: 0,
+            response_bytes: 2_000,
+            failed_assertions: Vec::new(),
+            failure: Some(FailureDetail {
+                at: std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+                headers,
+                body: b"overloaded".to_vec(),
+                body_truncated: true,
+                error: None,
+            }),
+        }];
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        let [ref sample] = report.failure_samples[..] else {
+            panic!("expected one sample: {:?}", report.failure_samples);
+        };
+        assert_eq!(sample.latency_nanos, 12_000_000);
+        assert_eq!(sample.status_code, 503);
+        assert!(!sample.success);
+        assert_eq!(sample.error_message, "");
+        assert_eq!(sample.timestamp_unix_nanos, 1_700_000_000_000_000_000);
+        let headers: Vec<(&str, &str)> = sample
+            .headers
+            .iter()
+            .map(|h| (h.name.as_str(), h.value.as_str()))
+            .collect();
+        assert_eq!(headers, [("retry-after", "5"), ("x-raw", "\u{fffd}")]);
+        assert_eq!(sample.body, b"overloaded");
+        assert!(sample.body_truncated);
+    }
+
+    #[test]
+    fn build_run_report_latency_over_time() {
+        let mut args = minimal_args("https://timeline.example/", "GET", 10, 5);
+        args.snapshot_interval = Duration::from_secs(1);
+        let mut result = run_result_empty();
+        let ms = Duration::from_millis;
+        result.latency_over_time = vec![LatencyPoint {
+            elapsed: Duration::from_secs(7),
+            requests: 40,
+            p50: ms(12),
+            p90: ms(30),
+            p99: ms(250),
+            max: ms(400),
+        }];
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        assert_eq!(
+            report.config.unwrap().snapshot_interval_nanos,
+            1_000_000_000
+        );
+        assert_eq!(
+            report.latency_over_time,
+            [ProtoLatencyPoint {
+                elapsed_nanos: 7_000_000_000,
+                requests: 40,
+                p50_nanos: 12_000_000,
+                p90_nanos: 30_000_000,
+                p99_nanos: 250_000_000,
+                max_nanos: 400_000_000,
+            }]
+        );
+    }
+
+    #[test]
+    fn build_run_report_phase_cdfs() {
+        let args = minimal_args("https://phases.example/", "GET", 10, 5);
+        let mut result = run_result_empty();
+        let ms = Duration::from_millis;
+        result.phase_times = PhaseTimes {
+            dns: latencies(&[ms(1)]),
+            connect: latencies(&[ms(2), ms(4)]),
+            tls: latencies(&[ms(3)]),
+            ttfb: latencies(&[ms(30), ms(10), ms(20)]),
+            body: Histogram::default(),
+        };
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        let max = |cdf: &[CdfPoint]| cdf.iter().map(|p| p.latency_nanos).max().unwrap();
+        assert_eq!(max(&report.cdf_dns), 1_000_000);
+        assert_eq!(max(&report.cdf_connect), 4_000_000);
+        assert_eq!(max(&report.cdf_tls), 3_000_000);
+        assert_eq!(max(&report.cdf_ttfb), 30_000_000);
+        assert!(report.cdf_body.is_empty());
+    }
+
+    #[test]
+    fn build_run_report_assertion_failures() {
+        let mut args = minimal_args("https://assert.example/", "GET", 10, 5);
+        args.assertions = vec![
+            Assertion::Status(vec![200]),
+            Assertion::BodyContains("ok".to_owned()),
+        ];
+        let mut result = run_result_full();
+        result.assertion_failures = BTreeMap::from([(1, 7)]);
+        result.worker_stats[0].assertion_failed = 7;
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        let failures: Vec<(&str, u64)> = report
+            .assertions
+            .iter()
+            .map(|a| (a.assertion.as_str(), a.failures))
+            .collect();
+        assert_eq!(
+            failures,
+            [("status in 200", 0), ("body contains \"ok\"", 7)]
+        );
+        assert_eq!(report.worker_stats[0].assertion_failed, 7);
+        assert_eq!(
+            report.config.unwrap().assertions,
+            ["status in 200", "body contains \"ok\""]
+        );
+    }
+
+    #[test]
+    fn build_run_report_aborted_run() {
+        let mut args = minimal_args("https://aborted.example/", "GET", 10, 60);
+        args.abort = Some(AbortPolicy {
+            error_rate: 0.5,
+            window: Duration::from_secs(10),
+        });
+        let mut result = run_result_empty();
+        result.status = RunStatus::Aborted;
+        result.abort = Some(AbortReason {
+            error_rate: 0.75,
+            threshold: 0.5,
+            window: Duration::from_secs(10),
+            requests: 40,
+            elapsed: Duration::from_secs(4),
+        });
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        assert_eq!(report.status(), ProtoRunStatus::Aborted);
+        assert_eq!(
+            report.abort_reason,
+            "error rate 75.0% over the last 10 seconds (40 requests) exceeded 50%"
+        );
+        let config = report.config.unwrap();
+        assert!(config.abort);
+        assert_eq!(config.abort_error_rate, 0.5);
+        assert_eq!(config.abort_window_nanos, 10_000_000_000);
+    }
+
+    #[test]
+    fn build_run_report_abort_on_any_error_is_kept_apart_from_no_abort() {
+        let mut args = minimal_args("https://aborted.example/", "GET", 10, 60);
+        let bytes = build_run_report(&args, &run_result_empty());
+        let config = LoadTestRunReport::decode(bytes.as_slice())
+            .unwrap()
+            .config
+            .unwrap();
+        assert!(!config.abort);
+
+        args.abort = Some(AbortPolicy {
+            error_rate: 0.0,
+            window: Duration::from_secs(10),
+        });
+        let bytes = build_run_report(&args, &run_result_empty());
+        let config = LoadTestRunReport::decode(bytes.as_slice())
+            .unwrap()
+            .config
+            .unwrap();
+        assert!(config.abort);
+        assert_eq!(config.abort_error_rate, 0.0);
+    }
+
+    #[test]
+    fn build_run_report_dropped_events() {
+        let args = minimal_args("https://events.example/", "GET", 10, 5);
+        let mut result = run_result_empty();
+        result.dropped_events = 12;
+        let bytes = build_run_report(&args, &result);
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        assert_eq!(report.dropped_events, 12);
+    }
+
+    /// Local HTTPS server (a self-signed certificate for localhost) answering every request with an
+    /// empty 200; returns its port.
+    fn serve_https() -> u16 {
+        use rustls::pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject as _};
+        use std::io::{Read as _, Write as _};
+
+        let cert =
+            CertificateDer::from_pem_slice(include_bytes!("../tests/data/localhost.pem")).unwrap();
+        let key =
+            PrivateKeyDer::from_pem_slice(include_bytes!("../tests/data/localhost.key")).unwrap();
+        let config = std::sync::Arc::new(
+            rustls::ServerConfig::builder_with_provider(std::sync::Arc::new(
+                rustls::crypto::aws_lc_rs::default_provider(),
+            ))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert], key)
+            .unwrap(),
+        );
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().map_while(Result::ok) {
+                let config = std::sync::Arc::clone(&config);
+                std::thread::spawn(move || {
+                    let connection = rustls::ServerConnection::new(config).unwrap();
+                    let mut tls = rustls::StreamOwned::new(connection, stream);
+                    let mut buffer = [0_u8; 4096];
+                    while tls.read(&mut buffer).is_ok_and(|read| read > 0) {
+                        let reply = b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n";
+                        if tls.write_all(reply).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        port
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn https_run_reports_its_tls_handshakes() {
+        crate::time_tls().unwrap();
+        let port = serve_https();
+        let mut args = minimal_args(&format!("https://localhost:{port}/"), "GET", 20, 1);
+        args.insecure = true;
+        args.requests = Some(3);
+        args.dry_run = false;
+        args.output =
+            std::env::temp_dir().join(format!("loadtest-https-{}.pb", std::process::id()));
+        crate::run(args.clone()).await.unwrap();
+
+        let bytes = std::fs::read(&args.output).unwrap();
+        std::fs::remove_file(&args.output).unwrap();
+        let report = LoadTestRunReport::decode(bytes.as_slice()).unwrap();
+        assert_eq!(
+            report
+                .worker_stats
+                .iter()
+                .map(|w| w.successful_response)
+                .sum::<u64>(),
+            3
+        );
+        assert!(!report.cdf_tls.is_empty());
+        assert!(report.cdf_tls.iter().all(|point| point.latency_nanos > 0));
+    }
 }
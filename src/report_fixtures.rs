@@ -1,8 +1,11 @@
 //! Hardcoded expected report structs for report tests.
 #![cfg(test)]
 
+use std::collections::BTreeMap;
+
 use crate::proto::{
-    CdfPoint, Header, LoadTestConfig, LoadTestRunReport, WorkerStats as ProtoWorkerStats,
+    ArrivalProcess as ProtoArrivalProcess, CdfPoint, Header, LoadTestConfig, LoadTestRunReport,
+    RunStatus as ProtoRunStatus, TerminationMode, WorkerStats as ProtoWorkerStats,
 };
 
 pub(crate) fn expected_full() -> LoadTestRunReport {
@@ -17,6 +20,32 @@ pub(crate) fn expected_full() -> LoadTestRunReport {
                 name: "x-foo".into(),
                 value: "Bar".into(),
             }],
+            concurrency: 0,
+            think_time_nanos: 0,
+            ramp_up_nanos: 0,
+            ramp_down_nanos: 0,
+            arrival: ProtoArrivalProcess::Constant.into(),
+            seed: 0,
+            max_in_flight: 0,
+            workers: 0,
+            threads: 0,
+            warmup_nanos: 0,
+            drain_timeout_nanos: 0,
+            termination: TerminationMode::Duration.into(),
+            requests: 0,
+            search: false,
+            slo_p99_nanos: 0,
+            slo_error_rate: 0.0,
+            search_tolerance: 0.0,
+            target_latency_nanos: 0,
+            target_percentile: 0.0,
+            adapt_interval_nanos: 0,
+            abort: false,
+            abort_error_rate: 0.0,
+            abort_window_nanos: 0,
+            assertions: vec![],
+            histogram_precision: 3,
+            snapshot_interval_nanos: 250000000,
         }),
         worker_stats: vec![ProtoWorkerStats {
             timestamp_unix_nanos: 1700000000000000000,
@@ -31,6 +60,18 @@ pub(crate) fn expected_full() -> LoadTestRunReport {
             server_error_response: 1,
             other_error_response: 2,
             timeouts: 0,
+            assertion_failed: 0,
+            request_bytes: 0,
+            response_bytes: 0,
+            status_codes: BTreeMap::new(),
+            connect_errors: 0,
+            dns_errors: 0,
+            tls_errors: 0,
+            connection_resets: 0,
+            body_errors: 0,
+            warmup: false,
+            dropped: 0,
+            abandoned: 0,
         }],
         cdf: vec![
             CdfPoint {
@@ -818,6 +859,31 @@ pub(crate) fn expected_full() -> LoadTestRunReport {
                 latency_nanos: 100000000,
             },
         ],
+        target_rate: vec![],
+        stages: vec![],
+        cdf_response_time: vec![],
+        status: ProtoRunStatus::Completed.into(),
+        run_duration_nanos: 0,
+        probes: vec![],
+        capacity_requests_per_second: 0.0,
+        abort_reason: String::new(),
+        status_codes: BTreeMap::new(),
+        cdf_dns: vec![],
+        cdf_connect: vec![],
+        cdf_tls: vec![],
+        cdf_ttfb: vec![],
+        cdf_body: vec![],
+        cdf_time_to_headers: vec![],
+        cdf_full_response: vec![],
+        request_bytes: 0,
+        response_bytes: 0,
+        cdf_response_size: vec![],
+        assertions: vec![],
+        failure_samples: vec![],
+        latency_over_time: vec![],
+        dropped_events: 0,
+        dropped_tickets: 0,
+        dropped_feedback: 0,
     }
 }
 
@@ -833,11 +899,62 @@ pub(crate) fn expected_empty() -> LoadTestRunReport {
                 name: "x-foo".into(),
                 value: "Bar".into(),
             }],
+            concurrency: 0,
+            think_time_nanos: 0,
+            ramp_up_nanos: 0,
+            ramp_down_nanos: 0,
+            arrival: ProtoArrivalProcess::Constant.into(),
+            seed: 0,
+            max_in_flight: 0,
+            workers: 0,
+            threads: 0,
+            warmup_nanos: 0,
+            drain_timeout_nanos: 0,
+            termination: TerminationMode::Duration.into(),
+            requests: 0,
+            search: false,
+            slo_p99_nanos: 0,
+            slo_error_rate: 0.0,
+            search_tolerance: 0.0,
+            target_latency_nanos: 0,
+            target_percentile: 0.0,
+            adapt_interval_nanos: 0,
+            abort: false,
+            abort_error_rate: 0.0,
+            abort_window_nanos: 0,
+            assertions: vec![],
+            histogram_precision: 3,
+            snapshot_interval_nanos: 250000000,
         }),
         worker_stats: vec![],
         cdf: vec![],
         cdf_success: vec![],
         cdf_non_success: vec![],
+        target_rate: vec![],
+        stages: vec![],
+        cdf_response_time: vec![],
+        status: ProtoRunStatus::Completed.into(),
+        run_duration_nanos: 0,
+        probes: vec![],
+        capacity_requests_per_second: 0.0,
+        abort_reason: String::new(),
+        status_codes: BTreeMap::new(),
+        cdf_dns: vec![],
+        cdf_connect: vec![],
+        cdf_tls: vec![],
+        cdf_ttfb: vec![],
+        cdf_body: vec![],
+        cdf_time_to_headers: vec![],
+        cdf_full_response: vec![],
+        request_bytes: 0,
+        response_bytes: 0,
+        cdf_response_size: vec![],
+        assertions: vec![],
+        failure_samples: vec![],
+        latency_over_time: vec![],
+        dropped_events: 0,
+        dropped_tickets: 0,
+        dropped_feedback: 0,
     }
 }
 
@@ -853,6 +970,32 @@ pub(crate) fn expected_multi_worker() -> LoadTestRunReport {
                 name: "x-foo".into(),
                 value: "Bar".into(),
             }],
+            concurrency: 0,
+            think_time_nanos: 0,
+            ramp_up_nanos: 0,
+            ramp_down_nanos: 0,
+            arrival: ProtoArrivalProcess::Constant.into(),
+            seed: 0,
+            max_in_flight: 0,
+            workers: 0,
+            threads: 0,
+            warmup_nanos: 0,
+            drain_timeout_nanos: 0,
+            termination: TerminationMode::Duration.into(),
+            requests: 0,
+            search: false,
+            slo_p99_nanos: 0,
+            slo_error_rate: 0.0,
+            search_tolerance: 0.0,
+            target_latency_nanos: 0,
+            target_percentile: 0.0,
+            adapt_interval_nanos: 0,
+            abort: false,
+            abort_error_rate: 0.0,
+            abort_window_nanos: 0,
+            assertions: vec![],
+            histogram_precision: 3,
+            snapshot_interval_nanos: 250000000,
         }),
         worker_stats: vec![
             ProtoWorkerStats {
@@ -868,6 +1011,18 @@ pub(crate) fn expected_multi_worker() -> LoadTestRunReport {
                 server_error_response: 5,
                 other_error_response: 5,
                 timeouts: 0,
+                assertion_failed: 0,
+                request_bytes: 0,
+                response_bytes: 0,
+                status_codes: BTreeMap::new(),
+                connect_errors: 0,
+                dns_errors: 0,
+                tls_errors: 0,
+                connection_resets: 0,
+                body_errors: 0,
+                warmup: false,
+                dropped: 0,
+                abandoned: 0,
             },
             ProtoWorkerStats {
                 timestamp_unix_nanos: 2000000000000,
@@ -882,6 +1037,18 @@ pub(crate) fn expected_multi_worker() -> LoadTestRunReport {
                 server_error_response: 2,
                 other_error_response: 3,
                 timeouts: 0,
+                assertion_failed: 0,
+                request_bytes: 0,
+                response_bytes: 0,
+                status_codes: BTreeMap::new(),
+                connect_errors: 0,
+                dns_errors: 0,
+                tls_errors: 0,
+                connection_resets: 0,
+                body_errors: 0,
+                warmup: false,
+                dropped: 0,
+                abandoned: 0,
             },
         ],
         cdf: vec![
@@ -1409,6 +1576,31 @@ pub(crate) fn expected_multi_worker() -> LoadTestRunReport {
             },
         ],
         cdf_non_success: vec![],
+        target_rate: vec![],
+        stages: vec![],
+        cdf_response_time: vec![],
+        status: ProtoRunStatus::Completed.into(),
+        run_duration_nanos: 0,
+        probes: vec![],
+        capacity_requests_per_second: 0.0,
+        abort_reason: String::new(),
+        status_codes: BTreeMap::new(),
+        cdf_dns: vec![],
+        cdf_connect: vec![],
+        cdf_tls: vec![],
+        cdf_ttfb: vec![],
+        cdf_body: vec![],
+        cdf_time_to_headers: vec![],
+        cdf_full_response: vec![],
+        request_bytes: 0,
+        response_bytes: 0,
+        cdf_response_size: vec![],
+        assertions: vec![],
+        failure_samples: vec![],
+        latency_over_time: vec![],
+        dropped_events: 0,
+        dropped_tickets: 0,
+        dropped_feedback: 0,
     }
 }
 
@@ -1424,6 +1616,32 @@ pub(crate) fn expected_cdf() -> LoadTestRunReport {
                 name: "x-foo".into(),
                 value: "Bar".into(),
             }],
+            concurrency: 0,
+            think_time_nanos: 0,
+            ramp_up_nanos: 0,
+            ramp_down_nanos: 0,
+            arrival: ProtoArrivalProcess::Constant.into(),
+            seed: 0,
+            max_in_flight: 0,
+            workers: 0,
+            threads: 0,
+            warmup_nanos: 0,
+            drain_timeout_nanos: 0,
+            termination: TerminationMode::Duration.into(),
+            requests: 0,
+            search: false,
+            slo_p99_nanos: 0,
+            slo_error_rate: 0.0,
+            search_tolerance: 0.0,
+            target_latency_nanos: 0,
+            target_percentile: 0.0,
+            adapt_interval_nanos: 0,
+            abort: false,
+            abort_error_rate: 0.0,
+            abort_window_nanos: 0,
+            assertions: vec![],
+            histogram_precision: 3,
+            snapshot_interval_nanos: 250000000,
         }),
         worker_stats: vec![],
         cdf: vec![
@@ -2212,6 +2430,31 @@ pub(crate) fn expected_cdf() -> LoadTestRunReport {
                 latency_nanos: 100000000,
             },
         ],
+        target_rate: vec![],
+        stages: vec![],
+        cdf_response_time: vec![],
+        status: ProtoRunStatus::Completed.into(),
+        run_duration_nanos: 0,
+        probes: vec![],
+        capacity_requests_per_second: 0.0,
+        abort_reason: String::new(),
+        status_codes: BTreeMap::new(),
+        cdf_dns: vec![],
+        cdf_connect: vec![],
+        cdf_tls: vec![],
+        cdf_ttfb: vec![],
+        cdf_body: vec![],
+        cdf_time_to_headers: vec![],
+        cdf_full_response: vec![],
+        request_bytes: 0,
+        response_bytes: 0,
+        cdf_response_size: vec![],
+        assertions: vec![],
+        failure_samples: vec![],
+        latency_over_time: vec![],
+        dropped_events: 0,
+        dropped_tickets: 0,
+        dropped_feedback: 0,
     }
 }
 
@@ -2224,6 +2467,32 @@ pub(crate) fn expected_no_headers() -> LoadTestRunReport {
             requests_per_second: 1,
             duration_secs: 1,
             headers: vec![],
+            concurrency: 0,
+            think_time_nanos: 0,
+            ramp_up_nanos: 0,
+            ramp_down_nanos: 0,
+            arrival: ProtoArrivalProcess::Constant.into(),
+            seed: 0,
+            max_in_flight: 0,
+            workers: 0,
+            threads: 0,
+            warmup_nanos: 0,
+            drain_timeout_nanos: 0,
+            termination: TerminationMode::Duration.into(),
+            requests: 0,
+            search: false,
+            slo_p99_nanos: 0,
+            slo_error_rate: 0.0,
+            search_tolerance: 0.0,
+            target_latency_nanos: 0,
+            target_percentile: 0.0,
+            adapt_interval_nanos: 0,
+            abort: false,
+            abort_error_rate: 0.0,
+            abort_window_nanos: 0,
+            assertions: vec![],
+            histogram_precision: 3,
+            snapshot_interval_nanos: 250000000,
         }),
         worker_stats: vec![],
         cdf: vec![
@@ -2751,6 +3020,31 @@ pub(crate) fn expected_no_headers() -> LoadTestRunReport {
             },
         ],
         cdf_non_success: vec![],
+        target_rate: vec![],
+        stages: vec![],
+        cdf_response_time: vec![],
+        status: ProtoRunStatus::Completed.into(),
+        run_duration_nanos: 0,
+        probes: vec![],
+        capacity_requests_per_second: 0.0,
+        abort_reason: String::new(),
+        status_codes: BTreeMap::new(),
+        cdf_dns: vec![],
+        cdf_connect: vec![],
+        cdf_tls: vec![],
+        cdf_ttfb: vec![],
+        cdf_body: vec![],
+        cdf_time_to_headers: vec![],
+        cdf_full_response: vec![],
+        request_bytes: 0,
+        response_bytes: 0,
+        cdf_response_size: vec![],
+        assertions: vec![],
+        failure_samples: vec![],
+        latency_over_time: vec![],
+        dropped_events: 0,
+        dropped_tickets: 0,
+        dropped_feedback: 0,
     }
 }
 
@@ -2766,6 +3060,32 @@ pub(crate) fn expected_epoch() -> LoadTestRunReport {
                 name: "x-foo".into(),
                 value: "Bar".into(),
             }],
+            concurrency: 0,
+            think_time_nanos: 0,
+            ramp_up_nanos: 0,
+            ramp_down_nanos: 0,
+            arrival: ProtoArrivalProcess::Constant.into(),
+            seed: 0,
+            max_in_flight: 0,
+            workers: 0,
+            threads: 0,
+            warmup_nanos: 0,
+            drain_timeout_nanos: 0,
+            termination: TerminationMode::Duration.into(),
+            requests: 0,
+            search: false,
+            slo_p99_nanos: 0,
+            slo_error_rate: 0.0,
+            search_tolerance: 0.0,
+            target_latency_nanos: 0,
+            target_percentile: 0.0,
+            adapt_interval_nanos: 0,
+            abort: false,
+            abort_error_rate: 0.0,
+            abort_window_nanos: 0,
+            assertions: vec![],
+            histogram_precision: 3,
+            snapshot_interval_nanos: 250000000,
         }),
         worker_stats: vec![],
         cdf: vec![
@@ -3293,6 +3613,31 @@ pub(crate) fn expected_epoch() -> LoadTestRunReport {
             },
         ],
         cdf_non_success: vec![],
+        target_rate: vec![],
+        stages: vec![],
+        cdf_response_time: vec![],
+        status: ProtoRunStatus::Completed.into(),
+        run_duration_nanos: 0,
+        probes: vec![],
+        capacity_requests_per_second: 0.0,
+        abort_reason: String::new(),
+        status_codes: BTreeMap::new(),
+        cdf_dns: vec![],
+        cdf_connect: vec![],
+        cdf_tls: vec![],
+        cdf_ttfb: vec![],
+        cdf_body: vec![],
+        cdf_time_to_headers: vec![],
+        cdf_full_response: vec![],
+        request_bytes: 0,
+        response_bytes: 0,
+        cdf_response_size: vec![],
+        assertions: vec![],
+        failure_samples: vec![],
+        latency_over_time: vec![],
+        dropped_events: 0,
+        dropped_tickets: 0,
+        dropped_feedback: 0,
     }
 }
 
@@ -3308,6 +3653,32 @@ pub(crate) fn expected_zero_rps() -> LoadTestRunReport {
                 name: "x-foo".into(),
                 value: "Bar".into(),
             }],
+            concurrency: 0,
+            think_time_nanos: 0,
+            ramp_up_nanos: 0,
+            ramp_down_nanos: 0,
+            arrival: ProtoArrivalProcess::Constant.into(),
+            seed: 0,
+            max_in_flight: 0,
+            workers: 0,
+            threads: 0,
+            warmup_nanos: 0,
+            drain_timeout_nanos: 0,
+            termination: TerminationMode::Duration.into(),
+            requests: 0,
+            search: false,
+            slo_p99_nanos: 0,
+            slo_error_rate: 0.0,
+            search_tolerance: 0.0,
+            target_latency_nanos: 0,
+            target_percentile: 0.0,
+            adapt_interval_nanos: 0,
+            abort: false,
+            abort_error_rate: 0.0,
+            abort_window_nanos: 0,
+            assertions: vec![],
+            histogram_precision: 3,
+            snapshot_interval_nanos: 250000000,
         }),
         worker_stats: vec![],
         cdf: vec![
@@ -3835,5 +4206,30 @@ pub(crate) fn expected_zero_rps() -> LoadTestRunReport {
             },
         ],
         cdf_non_success: vec![],
+        target_rate: vec![],
+        stages: vec![],
+        cdf_response_time: vec![],
+        status: ProtoRunStatus::Completed.into(),
+        run_duration_nanos: 0,
+        probes: vec![],
+        capacity_requests_per_second: 0.0,
+        abort_reason: String::new(),
+        status_codes: BTreeMap::new(),
+        cdf_dns: vec![],
+        cdf_connect: vec![],
+        cdf_tls: vec![],
+        cdf_ttfb: vec![],
+        cdf_body: vec![],
+        cdf_time_to_headers: vec![],
+        cdf_full_response: vec![],
+        request_bytes: 0,
+        response_bytes: 0,
+        cdf_response_size: vec![],
+        assertions: vec![],
+        failure_samples: vec![],
+        latency_over_time: vec![],
+        dropped_events: 0,
+        dropped_tickets: 0,
+        dropped_feedback: 0,
     }
 }
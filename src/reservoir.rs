@@ -0,0 +1,145 @@
+//! Bounded uniform sampling of a stream of unknown length (reservoir sampling).
+//!
+//! Each worker samples its own failed requests; the workers' reservoirs are merged at the end of the
+//! run into one uniform sample of the failures of the whole run.
+
+use rand::Rng as _;
+use rand::seq::SliceRandom as _;
+
+/// Uniform sample of at most `capacity` of the items offered so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reservoir<T> {
+    capacity: usize,
+    /// Items offered so far, sampled or not.
+    seen: u64,
+    items: Vec<T>,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: 0,
+            items: Vec::new(),
+        }
+    }
+
+    /// Offers one more item. `item` is only built if the item is sampled.
+    pub fn offer_with(&mut self, item: impl FnOnce() -> T) {
+        self.seen = self.seen.checked_add(1).expect("reservoir count overflow");
+        if self.items.len() < self.capacity {
+            self.items.push(item());
+            return;
+        }
+        // Algorithm R: the n-th item replaces a random slot with probability capacity / n.
+        let slot = rand::rng().random_range(0..self.seen);
+        if let Some(kept) = usize::try_from(slot)
+            .ok()
+            .and_then(|slot| self.items.get_mut(slot))
+        {
+            *kept = item();
+        }
+    }
+
+    /// Merges in the sample of another stream, leaving a uniform sample of both streams together.
+    pub fn merge(&mut self, other: Self) {
+        let mut rng = rand::rng();
+        let mut ours = std::mem::take(&mut self.items);
+        let mut theirs = other.items;
+        ours.shuffle(&mut rng);
+        theirs.shuffle(&mut rng);
+        let target = ours.len().saturating_add(theirs.len()).min(self.capacity);
+        // Draw without replacement from the union: each draw comes from a stream with probability
+        // proportional to its items not drawn yet. A reservoir holding fewer items than its stream
+        // is full, so it never runs out before `target` is reached.
+        let (mut our_left, mut their_left) = (self.seen, other.seen);
+        while self.items.len() < target {
+            let Some(left) = our_left.checked_add(their_left).filter(|&left| left > 0) else {
+                break;
+            };
+            let item = if rng.random_range(0..left) < our_left {
+                our_left = our_left.saturating_sub(1);
+                ours.pop()
+            } else {
+                their_left = their_left.saturating_sub(1);
+                theirs.pop()
+            };
+            self.items.extend(item);
+        }
+        self.seen = self
+            .seen
+            .checked_add(other.seen)
+            .expect("reservoir count overflow");
+    }
+
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(capacity: usize, items: std::ops::Range<u32>) -> Reservoir<u32> {
+        let mut reservoir = Reservoir::new(capacity);
+        for item in items {
+            reservoir.offer_with(|| item);
+        }
+        reservoir
+    }
+
+    #[test]
+    fn keeps_everything_below_capacity() {
+        let reservoir = filled(10, 0..4);
+        assert_eq!(reservoir.items, [0, 1, 2, 3]);
+        assert_eq!(reservoir.seen, 4);
+    }
+
+    #[test]
+    fn stays_bounded() {
+        let reservoir = filled(10, 0..1_000);
+        assert_eq!(reservoir.items.len(), 10);
+        assert_eq!(reservoir.seen, 1_000);
+        assert!(reservoir.items.iter().all(|&item| item < 1_000));
+    }
+
+    #[test]
+    fn sample_is_uniform() {
+        // Over many runs, each item of 0..100 is sampled about capacity / 100 of the time.
+        let mut hits = [0_u32; 100];
+        for _ in 0..2_000 {
+            for item in filled(10, 0..100).items {
+                hits[usize::try_from(item).unwrap()] += 1;
+            }
+        }
+        // Expected 200 each.
+        assert!(
+            hits.iter().all(|&count| (100..300).contains(&count)),
+            "{hits:?}"
+        );
+    }
+
+    #[test]
+    fn merge_weighs_streams_by_their_size() {
+        let mut from_small = 0;
+        for _ in 0..1_000 {
+            let mut merged = filled(10, 0..10);
+            merged.merge(filled(10, 10..100));
+            assert_eq!(merged.items.len(), 10);
+            assert_eq!(merged.seen, 100);
+            from_small += merged.items.iter().filter(|&&item| item < 10).count();
+        }
+        // A tenth of the failures came from the small stream: expected 1000 of 10 000.
+        assert!((700..1_300).contains(&from_small), "{from_small}");
+    }
+
+    #[test]
+    fn merge_keeps_everything_below_capacity() {
+        let mut merged = filled(10, 0..3);
+        merged.merge(filled(10, 3..5));
+        let mut items = merged.into_items();
+        items.sort_unstable();
+        assert_eq!(items, [0, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,392 @@
+//! Throughput search: the highest request rate the target sustains within latency and error SLOs.
+//!
+//! Each probe is a full open-loop run of `--duration` at one rate. The rate doubles from
+//! `--requests-per-second` until a probe misses an SLO, then bisects between the highest passing
+//! and the lowest failing rate until they are within `--search-tolerance` of each other. A search
+//! runs at most [`MAX_PROBES`] probes, so it can take that many times `--duration`.
+//!
+//! A probe aborted by `--abort-on-error-rate` fails without being measured against the SLOs. An
+//! interrupted probe is not scored at all, and ends the search.
+
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive as _;
+
+use crate::cdf;
+use crate::cli::Args;
+use crate::error::Result;
+use crate::events::EventLog;
+use crate::report::{RESOLUTION, START_PERCENTILE, STEPS};
+use crate::worker_manager::{RunResult, RunStatus, spawn_workers};
+use tokio_util::sync::CancellationToken;
+
+/// Throughput search settings (--search).
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Search {
+    /// SLO on the p99 response time.
+    pub slo_p99: Option<Duration>,
+    /// SLO on the share of requests without a successful response, as a fraction.
+    pub slo_error_rate: Option<f64>,
+    /// The search stops once the highest passing and lowest failing rates are within this fraction
+    /// of each other.
+    pub tolerance: f64,
+}
+
+/// Upper bound on probe runs, in case the tolerance is too tight to ever be met.
+pub(crate) const MAX_PROBES: usize = 16;
+
+/// Lowest rate probed when even the starting rate misses the SLOs.
+const MIN_RATE: Decimal = Decimal::from_parts(1, 0, 0, false, 1);
+
+/// Percentile the latency SLO applies to.
+const SLO_PERCENTILE: f64 = 0.99;
+
+/// Outcome of one probe run.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Probe {
+    pub requests_per_second: Decimal,
+    /// p99 response time, or `None` if no request completed.
+    pub p99: Option<Duration>,
+    /// Share of completed requests without a successful response.
+    pub error_rate: f64,
+    /// Completed requests measured (warm-up excluded).
+    pub requests: u64,
+    pub passed: bool,
+    /// How the probe run ended. Only completed probes are measured against the SLOs; an aborted
+    /// one fails with the error rate and request count of its abort window, and an interrupted one
+    /// is not scored.
+    pub status: RunStatus,
+}
+
+/// Every probe in the order it ran, and the highest passing rate if any probe passed.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SearchResult {
+    pub probes: Vec<Probe>,
+    pub capacity: Option<Decimal>,
+}
+
+/// Runs the search. The returned run is the probe at the chosen capacity (or the last probe if none
+/// passed), with every probe attached in `search`. Every probe's requests go to `events`.
+/// Cancelling `interrupt` stops the probe in progress and the search.
+pub(crate) async fn search(
+    args: &Args,
+    settings: Search,
+    events: Option<&EventLog>,
+    interrupt: &CancellationToken,
+) -> Result<RunResult> {
+    let mut probes = Vec::new();
+    let mut passing: Option<(Decimal, RunResult)> = None;
+    let mut failing: Option<(Decimal, RunResult)> = None;
+    let mut interrupted = None;
+    let mut rate = args.requests_per_second;
+    while probes.len() < MAX_PROBES {
+        println!(
+            "Probing {rate} requests/second for {} seconds",
+            args.duration.as_secs()
+        );
+        let probe_args = Args {
+            requests_per_second: rate,
+            ..args.clone()
+        };
+        let mut result = spawn_workers(&probe_args, events, interrupt).await?;
+        if interrupt.is_cancelled() {
+            result.status = RunStatus::Interrupted;
+        }
+        let probe = score(settings, rate, &result);
+        println!("  {}", format_probe(&probe));
+        probes.push(probe.clone());
+        if probe.status == RunStatus::Interrupted {
+            interrupted = Some(result);
+            break;
+        }
+        if probe.passed {
+            passing = Some((rate, result));
+        } else {
+            failing = Some((rate, result));
+        }
+        let Some(next) = next_rate(
+            passing.as_ref().map(|&(rate, _)| rate),
+            failing.as_ref().map(|&(rate, _)| rate),
+            settings.tolerance,
+        ) else {
+            break;
+        };
+        rate = next;
+    }
+
+    let capacity = passing.as_ref().map(|&(rate, _)| rate);
+    let stopped = interrupted.is_some();
+    let mut result = passing
+        .or(failing)
+        .map(|(_, result)| result)
+        .or(interrupted)
+        .expect("the search runs at least one probe");
+    if stopped {
+        result.status = RunStatus::Interrupted;
+    }
+    result.search = Some(SearchResult { probes, capacity });
+    result.dropped_events = events.map_or(0, EventLog::dropped);
+    Ok(result)
+}
+
+/// Scores a probe run: measured against the SLOs if it completed, failed if it was aborted, and
+/// neither passed nor failed if it was interrupted.
+fn score(settings: Search, requests_per_second: Decimal, result: &RunResult) -> Probe {
+    if result.status == RunStatus::Interrupted {
+        return Probe {
+            requests_per_second,
+            p99: None,
+            error_rate: 0.0_f64,
+            requests: 0,
+            passed: false,
+            status: RunStatus::Interrupted,
+        };
+    }
+    let Some(ref abort) = result.abort else {
+        return evaluate(settings, requests_per_second, result);
+    };
+    Probe {
+        requests_per_second,
+        p99: None,
+        error_rate: abort.error_rate,
+        requests: u64::try_from(abort.requests).expect("request count fits in u64"),
+        passed: false,
+        status: RunStatus::Aborted,
+    }
+}
+
+/// Measures a probe run against the SLOs. A probe in which no request completed fails.
+fn evaluate(settings: Search, requests_per_second: Decimal, result: &RunResult) -> Probe {
+    let cdf = cdf::calculate_cdf(START_PERCENTILE, RESOLUTION, STEPS, &result.response_times);
+    // The CDF is log-spaced in the tail; allow for float rounding of its p99 point.
+    let p99 = cdf
+        .iter()
+        .find(|&&(percentile, _)| percentile >= SLO_PERCENTILE - 1e-9_f64)
+        .map(|&(_, nanos)| Duration::from_nanos(nanos));
+    let failed = result.non_success_latencies.len();
+    let requests = result
+        .success_latencies
+        .len()
+        .checked_add(failed)
+        .expect("request count overflow");
+    let error_rate = if requests == 0 {
+        1.0_f64
+    } else {
+        failed.to_f64().expect("count fits in f64") / requests.to_f64().expect("count fits in f64")
+    };
+    let latency_ok = match (settings.slo_p99, p99) {
+        (Some(slo), Some(p99)) => p99 <= slo,
+        (Some(_), None) => false,
+        (None, _) => true,
+    };
+    let errors_ok = settings.slo_error_rate.is_none_or(|slo| error_rate <= slo);
+    Probe {
+        requests_per_second,
+        p99,
+        error_rate,
+        requests,
+        passed: requests > 0 && latency_ok && errors_ok,
+        status: RunStatus::Completed,
+    }
+}
+
+/// Next rate to probe given the highest passing and lowest failing rates so far, or `None` once the
+/// search has converged (or cannot go lower).
+fn next_rate(
+    passing: Option<Decimal>,
+    failing: Option<Decimal>,
+    tolerance: f64,
+) -> Option<Decimal> {
+    match (passing, failing) {
+        (Some(low), None) => low.checked_mul(Decimal::TWO),
+        (None, Some(high)) => high
+            .checked_div(Decimal::TWO)
+            .filter(|&rate| rate >= MIN_RATE),
+        (Some(low), Some(high)) => {
+            let gap = high.checked_sub(low)?.checked_div(high)?.to_f64()?;
+            if gap <= tolerance {
+                return None;
+            }
+            let mid = low
+                .checked_add(high)?
+                .checked_div(Decimal::TWO)?
+                .round_dp(3);
+            (low < mid && mid < high).then_some(mid)
+        }
+        (None, None) => None,
+    }
+}
+
+fn format_probe(probe: &Probe) -> String {
+    match probe.status {
+        RunStatus::Completed => {}
+        RunStatus::Interrupted => return "interrupted: not scored".to_owned(),
+        RunStatus::Aborted => {
+            return format!(
+                "fail: aborted at {:.2}% errors over {} requests",
+                probe.error_rate * 100.0_f64,
+                probe.requests
+            );
+        }
+    }
+    let p99 = probe.p99.map_or_else(
+        || "n/a".to_owned(),
+        |p99| format!("{:.3} ms", p99.as_secs_f64() * 1e3_f64),
+    );
+    format!(
+        "{}: p99 {p99}, errors {:.2}% over {} requests",
+        if probe.passed { "pass" } else { "fail" },
+        probe.error_rate * 100.0_f64,
+        probe.requests
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, time::SystemTime};
+
+    use super::*;
+    use crate::{abort::AbortReason, histogram::Histogram, stats::PhaseTimes};
+
+    fn rate(n: i64) -> Decimal {
+        Decimal::from(n)
+    }
+
+    fn settings() -> Search {
+        Search {
+            slo_p99: Some(Duration::from_millis(50)),
+            slo_error_rate: Some(0.01),
+            tolerance: 0.05,
+        }
+    }
+
+    /// A probe run with `ok` fast successes and `failed` slow failures.
+    fn run(status: RunStatus, ok: u64, failed: u64) -> RunResult {
+        let mut success_latencies = Histogram::default();
+        let mut non_success_latencies = Histogram::default();
+        let mut response_times = Histogram::default();
+        for _ in 0..ok {
+            success_latencies.record_duration(Duration::from_millis(10));
+            response_times.record_duration(Duration::from_millis(10));
+        }
+        for _ in 0..failed {
+            non_success_latencies.record_duration(Duration::from_millis(10));
+            response_times.record_duration(Duration::from_millis(10));
+        }
+        RunResult {
+            run_timestamp: SystemTime::UNIX_EPOCH,
+            status,
+            run_duration: Duration::from_secs(10),
+            worker_stats: Vec::new(),
+            success_latencies,
+            non_success_latencies,
+            response_times,
+            target_rate: Vec::new(),
+            status_codes: BTreeMap::new(),
+            assertion_failures: BTreeMap::new(),
+            time_to_headers: Histogram::default(),
+            full_response: Histogram::default(),
+            request_bytes: 0,
+            response_bytes: 0,
+            response_sizes: Histogram::default(),
+            phase_times: PhaseTimes::default(),
+            failure_samples: Vec::new(),
+            latency_over_time: Vec::new(),
+            search: None,
+            abort: None,
+            dropped_events: 0,
+            dropped_tickets: 0,
+            dropped_feedback: 0,
+        }
+    }
+
+    #[test]
+    fn score_measures_completed_probes_against_the_slos() {
+        let probe = score(settings(), rate(100), &run(RunStatus::Completed, 1000, 0));
+        assert!(probe.passed);
+        assert_eq!(probe.status, RunStatus::Completed);
+        assert_eq!(probe.p99, Some(Duration::from_millis(10)));
+        let probe = score(settings(), rate(100), &run(RunStatus::Completed, 900, 100));
+        assert!(!probe.passed);
+    }
+
+    #[test]
+    fn score_fails_aborted_probes_with_their_abort_window() {
+        // Within the SLOs over the whole run, but aborted: it still fails.
+        let mut result = run(RunStatus::Aborted, 1000, 0);
+        result.abort = Some(AbortReason {
+            error_rate: 0.6,
+            threshold: 0.5,
+            window: Duration::from_secs(10),
+            requests: 40,
+            elapsed: Duration::from_secs(3),
+        });
+        let probe = score(settings(), rate(100), &result);
+        assert!(!probe.passed);
+        assert_eq!(probe.status, RunStatus::Aborted);
+        assert_eq!((probe.p99, probe.requests), (None, 40));
+        assert!((probe.error_rate - 0.6).abs() < 1e-12);
+    }
+
+    #[test]
+    fn score_leaves_interrupted_probes_unscored() {
+        let probe = score(settings(), rate(100), &run(RunStatus::Interrupted, 1000, 0));
+        assert!(!probe.passed);
+        assert_eq!(probe.status, RunStatus::Interrupted);
+        assert_eq!(format_probe(&probe), "interrupted: not scored");
+    }
+
+    #[test]
+    fn next_rate_doubles_while_passing() {
+        assert_eq!(next_rate(Some(rate(100)), None, 0.05), Some(rate(200)));
+    }
+
+    #[test]
+    fn next_rate_halves_while_failing() {
+        assert_eq!(next_rate(None, Some(rate(100)), 0.05), Some(rate(50)));
+        assert_eq!(next_rate(None, Some(Decimal::new(1, 1)), 0.05), None);
+    }
+
+    #[test]
+    fn next_rate_bisects_between_pass_and_fail() {
+        assert_eq!(
+            next_rate(Some(rate(200)), Some(rate(400)), 0.05),
+            Some(rate(300))
+        );
+    }
+
+    #[test]
+    fn next_rate_stops_within_tolerance() {
+        assert_eq!(next_rate(Some(rate(96)), Some(rate(100)), 0.05), None);
+        assert_eq!(
+            next_rate(Some(rate(90)), Some(rate(100)), 0.05),
+            Some(rate(95))
+        );
+    }
+
+    #[test]
+    fn bisection_converges_on_the_knee() {
+        // A target that sustains up to 730 rps.
+        let knee = rate(730);
+        let (mut passing, mut failing) = (None, None);
+        let mut current = rate(100);
+        let mut probes = 0;
+        loop {
+            probes += 1;
+            if current <= knee {
+                passing = Some(current);
+            } else {
+                failing = Some(current);
+            }
+            match next_rate(passing, failing, 0.02) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        let capacity = passing.unwrap();
+        assert!(capacity <= knee && capacity >= rate(715), "{capacity}");
+        assert!(probes <= MAX_PROBES, "{probes}");
+    }
+}
@@ -0,0 +1,167 @@
+//! Multi-stage load profiles read from a `--stages` file.
+//!
+//! Each stage is `DURATION@TARGET`, one per line (or comma-separated), executed back to back:
+//!
+//! ```text
+//! # warm up, hold, cool down
+//! 1m@100rps
+//! 5m@500rps
+//! 1m@0
+//! ```
+//!
+//! Durations take an `ms`, `s`, `m` or `h` suffix (bare numbers are seconds). Targets are a request
+//! rate (`rps` suffix, or a bare number) or a number of closed-loop virtual users (`vu` suffix).
+//! All stages of a file must use the same kind of target.
+
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+
+use crate::error::{AppError, Result};
+
+/// One stage of a multi-stage run.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stage {
+    pub duration: Duration,
+    pub target: StageTarget,
+}
+
+/// What a stage holds constant for its duration.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StageTarget {
+    /// Open-loop request rate (requests per second).
+    Rate(Decimal),
+    /// Number of closed-loop virtual users.
+    Users(usize),
+}
+
+/// Parses the contents of a stages file. Blank lines and `#` comments are ignored.
+pub(crate) fn parse_stages(text: &str) -> Result<Vec<Stage>> {
+    let mut stages = Vec::new();
+    for line in text.lines() {
+        let line = line.split_once('#').map_or(line, |(before, _)| before);
+        for raw in line.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            stages.push(parse_stage(raw)?);
+        }
+    }
+    if stages.is_empty() {
+        return Err(AppError::NoStages);
+    }
+    let users = stages
+        .iter()
+        .filter(|s| matches!(s.target, StageTarget::Users(_)))
+        .count();
+    if users != 0 && users != stages.len() {
+        return Err(AppError::MixedStageTargets);
+    }
+    Ok(stages)
+}
+
+/// Parses a single `DURATION@TARGET` stage, e.g. `30s@200rps` or `2m@50vu`.
+fn parse_stage(raw: &str) -> Result<Stage> {
+    let invalid = || AppError::InvalidStage {
+        raw: raw.to_owned(),
+    };
+    let (duration, target) = raw.split_once('@').ok_or_else(invalid)?;
+    let duration = parse_stage_duration(duration.trim()).ok_or_else(invalid)?;
+    let target = target.trim();
+    let target = if let Some(users) = target.strip_suffix("vu") {
+        StageTarget::Users(users.trim().parse().ok().ok_or_else(invalid)?)
+    } else {
+        let rate = target.strip_suffix("rps").unwrap_or(target).trim();
+        let rate: Decimal = rate.parse().ok().ok_or_else(invalid)?;
+        if rate.is_sign_negative() {
+            return Err(invalid());
+        }
+        StageTarget::Rate(rate)
+    };
+    Ok(Stage { duration, target })
+}
+
+/// Parses `500ms`, `30s`, `5m`, `1h` or a bare number of seconds. Returns `None` if invalid or zero.
+fn parse_stage_duration(s: &str) -> Option<Duration> {
+    let (value, unit_secs) = if let Some(v) = s.strip_suffix("ms") {
+        (v, 0.001_f64)
+    } else if let Some(v) = s.strip_suffix('s') {
+        (v, 1.0_f64)
+    } else if let Some(v) = s.strip_suffix('m') {
+        (v, 60.0_f64)
+    } else if let Some(v) = s.strip_suffix('h') {
+        (v, 3600.0_f64)
+    } else {
+        (s, 1.0_f64)
+    };
+    let value: f64 = value.trim().parse().ok()?;
+    let duration = Duration::try_from_secs_f64(value * unit_secs).ok()?;
+    (!duration.is_zero()).then_some(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(secs: u64, rps: i64) -> Stage {
+        Stage {
+            duration: Duration::from_secs(secs),
+            target: StageTarget::Rate(Decimal::from(rps)),
+        }
+    }
+
+    #[test]
+    fn parse_rate_stages_with_units_and_comments() {
+        let stages = parse_stages("# ramp\n1m@100rps\n5m@500rps\n\n1m@0 # cool down\n").unwrap();
+        assert_eq!(stages, vec![rate(60, 100), rate(300, 500), rate(60, 0)]);
+    }
+
+    #[test]
+    fn parse_comma_separated_user_stages() {
+        let stages = parse_stages("30s@10vu, 1h@50vu").unwrap();
+        assert_eq!(
+            stages,
+            vec![
+                Stage {
+                    duration: Duration::from_secs(30),
+                    target: StageTarget::Users(10),
+                },
+                Stage {
+                    duration: Duration::from_hours(1),
+                    target: StageTarget::Users(50),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_fractional_rate_and_millis() {
+        let stages = parse_stages("1500ms@0.5").unwrap();
+        assert_eq!(stages[0].duration, Duration::from_millis(1500));
+        assert_eq!(stages[0].target, StageTarget::Rate(Decimal::new(5, 1)));
+    }
+
+    #[test]
+    fn parse_stages_errors() {
+        assert!(matches!(parse_stages(""), Err(AppError::NoStages)));
+        assert!(matches!(
+            parse_stages("1m@10rps\n1m@5vu"),
+            Err(AppError::MixedStageTargets)
+        ));
+        assert!(matches!(
+            parse_stages("1m"),
+            Err(AppError::InvalidStage { .. })
+        ));
+        assert!(matches!(
+            parse_stages("0s@10"),
+            Err(AppError::InvalidStage { .. })
+        ));
+        assert!(matches!(
+            parse_stages("1m@-5rps"),
+            Err(AppError::InvalidStage { .. })
+        ));
+        assert!(matches!(
+            parse_stages("1m@lots"),
+            Err(AppError::InvalidStage { .. })
+        ));
+    }
+}
@@ -1,8 +1,14 @@
+use std::collections::BTreeMap;
 use std::time::{Duration, SystemTime};
 
-use crate::work_unit::{ExecutionResult, HttpRequestOutcome};
+use crate::histogram::{DEFAULT_PRECISION, Histogram};
+use crate::reservoir::Reservoir;
+use crate::work_unit::{ExecutionResult, HttpRequestOutcome, Phases};
 
-#[derive(Debug, PartialEq, Eq)]
+/// Failed requests sampled per worker, and over the whole run once the workers' samples are merged.
+pub const FAILURE_SAMPLES: usize = 100;
+
+#[derive(Debug, PartialEq)]
 pub struct Statistics {
     pub informational_response: u64,
     pub successful_response: u64,
@@ -11,12 +17,51 @@ pub struct Statistics {
     pub server_error_response: u64,
     pub other_error_response: u64,
     pub timeouts: u64,
-    pub success_latencies: Vec<Duration>,
-    pub non_success_latencies: Vec<Duration>,
+    /// Requests that could not connect, e.g. because the connection was refused.
+    pub connect_errors: u64,
+    /// Requests whose host name could not be resolved.
+    pub dns_errors: u64,
+    /// Requests whose TLS handshake failed, e.g. on an untrusted certificate.
+    pub tls_errors: u64,
+    /// Requests whose connection was reset or closed before a response arrived.
+    pub connection_resets: u64,
+    /// Responses whose body failed to arrive in full.
+    pub body_errors: u64,
+    /// Responses that failed an --expect-* assertion.
+    pub assertion_failed: u64,
+    /// Requests the schedule called for but that were not sent because --max-in-flight was reached.
+    pub dropped: u64,
+    /// Requests still in flight when the drain timeout expired; cancelled and never completed.
+    pub abandoned: u64,
+    /// Responses per exact HTTP status code.
+    pub status_codes: BTreeMap<u16, u64>,
+    /// Failures per assertion, keyed by its index in `Args::assertions`.
+    pub assertion_failures: BTreeMap<usize, u64>,
+    /// Request bytes sent, counted as HTTP/1.1 messages (an estimate over HTTP/2).
+    pub request_bytes: u64,
+    /// Response body bytes received, partial bodies included.
+    pub response_bytes: u64,
+    pub success_latencies: Histogram,
+    pub non_success_latencies: Histogram,
+    /// Response times (measured from the intended send time) of all requests.
+    pub response_times: Histogram,
+    /// Service time until the response headers, of requests that received them.
+    pub time_to_headers: Histogram,
+    /// Service time until the end of the body, of requests that received a full response.
+    pub full_response: Histogram,
+    /// Body sizes of full responses, in bytes.
+    pub response_sizes: Histogram,
+    /// Time spent in each phase, over all requests.
+    pub phase_times: PhaseTimes,
+    /// Latencies of the requests added since the worker's last snapshot.
+    pub window: Histogram,
+    /// Uniform sample of the failed requests, warm-up included.
+    pub failures: Reservoir<ExecutionResult>,
 }
 
 impl Statistics {
-    pub fn new(capacity: usize) -> Self {
+    /// Empty statistics whose histograms keep `precision` significant decimal digits.
+    pub fn new(precision: u8) -> Self {
         Self {
             successful_response: 0,
             informational_response: 0,
@@ -25,13 +70,74 @@ impl Statistics {
             server_error_response: 0,
             other_error_response: 0,
             timeouts: 0,
-            success_latencies: Vec::with_capacity(capacity),
-            non_success_latencies: Vec::with_capacity(capacity),
+            connect_errors: 0,
+            dns_errors: 0,
+            tls_errors: 0,
+            connection_resets: 0,
+            body_errors: 0,
+            assertion_failed: 0,
+            dropped: 0,
+            abandoned: 0,
+            status_codes: BTreeMap::new(),
+            assertion_failures: BTreeMap::new(),
+            request_bytes: 0,
+            response_bytes: 0,
+            success_latencies: Histogram::new(precision),
+            non_success_latencies: Histogram::new(precision),
+            response_times: Histogram::new(precision),
+            time_to_headers: Histogram::new(precision),
+            full_response: Histogram::new(precision),
+            response_sizes: Histogram::new(precision),
+            phase_times: PhaseTimes::new(precision),
+            window: Histogram::new(precision),
+            failures: Reservoir::new(FAILURE_SAMPLES),
         }
     }
 
     pub fn add(&mut self, result: &ExecutionResult) {
-        let is_success = result.outcome == HttpRequestOutcome::SuccessResponse;
+        self.count(result);
+        if result.outcome == HttpRequestOutcome::SuccessResponse {
+            self.success_latencies.record_duration(result.duration);
+        } else {
+            self.non_success_latencies.record_duration(result.duration);
+        }
+        self.response_times.record_duration(result.response_time());
+        if let Some(time_to_headers) = result.time_to_headers {
+            self.time_to_headers.record_duration(time_to_headers);
+        }
+        if result.is_full_response() {
+            self.full_response.record_duration(result.duration);
+            self.response_sizes.record(result.response_bytes);
+        }
+        self.phase_times.record(&result.phases);
+        self.window.record_duration(result.duration);
+    }
+
+    /// Adds a warm-up request: it is counted, but its latency is left out of the distributions.
+    pub fn add_warmup(&mut self, result: &ExecutionResult) {
+        self.count(result);
+    }
+
+    fn count(&mut self, result: &ExecutionResult) {
+        if result.failure.is_some() {
+            self.failures.offer_with(|| result.clone());
+        }
+        if let Some(status_code) = result.status_code {
+            let count = self.status_codes.entry(status_code).or_insert(0);
+            *count = count.checked_add(1).expect("status code counter overflow");
+        }
+        for &index in &result.failed_assertions {
+            let count = self.assertion_failures.entry(index).or_insert(0);
+            *count = count.checked_add(1).expect("assertion counter overflow");
+        }
+        self.request_bytes = self
+            .request_bytes
+            .checked_add(result.request_bytes)
+            .expect("request_bytes counter overflow");
+        self.response_bytes = self
+            .response_bytes
+            .checked_add(result.response_bytes)
+            .expect("response_bytes counter overflow");
         match result.outcome {
             HttpRequestOutcome::InformationalResponse => {
                 self.informational_response = self
@@ -75,13 +181,113 @@ impl Statistics {
                     .checked_add(1)
                     .expect("timeouts counter overflow");
             }
+            HttpRequestOutcome::ConnectError => {
+                self.connect_errors = self
+                    .connect_errors
+                    .checked_add(1)
+                    .expect("connect_errors counter overflow");
+            }
+            HttpRequestOutcome::DnsError => {
+                self.dns_errors = self
+                    .dns_errors
+                    .checked_add(1)
+                    .expect("dns_errors counter overflow");
+            }
+            HttpRequestOutcome::TlsError => {
+                self.tls_errors = self
+                    .tls_errors
+                    .checked_add(1)
+                    .expect("tls_errors counter overflow");
+            }
+            HttpRequestOutcome::ConnectionReset => {
+                self.connection_resets = self
+                    .connection_resets
+                    .checked_add(1)
+                    .expect("connection_resets counter overflow");
+            }
+            HttpRequestOutcome::BodyError => {
+                self.body_errors = self
+                    .body_errors
+                    .checked_add(1)
+                    .expect("body_errors counter overflow");
+            }
+            HttpRequestOutcome::AssertionFailed => {
+                self.assertion_failed = self
+                    .assertion_failed
+                    .checked_add(1)
+                    .expect("assertion_failed counter overflow");
+            }
         }
-        if is_success {
-            self.success_latencies.push(result.duration);
-        } else {
-            self.non_success_latencies.push(result.duration);
+    }
+
+    /// Counts a request that was dropped because the client was saturated.
+    pub fn add_dropped(&mut self) {
+        self.dropped = self
+            .dropped
+            .checked_add(1)
+            .expect("dropped counter overflow");
+    }
+
+    /// Counts `count` requests cancelled because they outlived the drain timeout.
+    pub fn add_abandoned(&mut self, count: usize) {
+        self.abandoned = self
+            .abandoned
+            .checked_add(u64::try_from(count).expect("abandoned count fits in u64"))
+            .expect("abandoned counter overflow");
+    }
+}
+
+impl Default for Statistics {
+    #[inline]
+    fn default() -> Self {
+        Self::new(DEFAULT_PRECISION)
+    }
+}
+
+/// Durations of each request phase. A request adds nothing for a phase it did not go through (e.g.
+/// DNS, connect and TLS on a pooled connection).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PhaseTimes {
+    pub dns: Histogram,
+    pub connect: Histogram,
+    pub tls: Histogram,
+    pub ttfb: Histogram,
+    pub body: Histogram,
+}
+
+impl PhaseTimes {
+    pub fn new(precision: u8) -> Self {
+        Self {
+            dns: Histogram::new(precision),
+            connect: Histogram::new(precision),
+            tls: Histogram::new(precision),
+            ttfb: Histogram::new(precision),
+            body: Histogram::new(precision),
         }
     }
+
+    pub fn record(&mut self, phases: &Phases) {
+        let phases = [
+            (&mut self.dns, phases.dns),
+            (&mut self.connect, phases.connect),
+            (&mut self.tls, phases.tls),
+            (&mut self.ttfb, phases.ttfb),
+            (&mut self.body, phases.body),
+        ];
+        for (histogram, duration) in phases {
+            if let Some(duration) = duration {
+                histogram.record_duration(duration);
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.dns.merge(&other.dns);
+        self.connect.merge(&other.connect);
+        self.tls.merge(&other.tls);
+        self.ttfb.merge(&other.ttfb);
+        self.body.merge(&other.body);
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -98,23 +304,64 @@ pub struct WorkerStats {
     pub server_error_response: u64,
     pub other_error_response: u64,
     pub timeouts: u64,
+    pub connect_errors: u64,
+    pub dns_errors: u64,
+    pub tls_errors: u64,
+    pub connection_resets: u64,
+    pub body_errors: u64,
+    pub assertion_failed: u64,
+    pub dropped: u64,
+    pub abandoned: u64,
+    /// Responses per exact HTTP status code so far.
+    pub status_codes: BTreeMap<u16, u64>,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    /// Snapshot taken during the warm-up period.
+    pub warmup: bool,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::work_unit::{ExecutionResult, HttpRequestOutcome};
+    use crate::work_unit::{ExecutionResult, FailureDetail, HttpRequestOutcome, Phases};
+    use std::time::Instant;
+
+    fn histogram(durations: &[Duration]) -> Histogram {
+        let mut histogram = Histogram::default();
+        for &duration in durations {
+            histogram.record_duration(duration);
+        }
+        histogram
+    }
+
+    fn sizes(sizes: &[u64]) -> Histogram {
+        let mut histogram = Histogram::default();
+        for &size in sizes {
+            histogram.record(size);
+        }
+        histogram
+    }
 
     fn result(outcome: HttpRequestOutcome, duration_nanos: u64) -> ExecutionResult {
+        let now = Instant::now();
         ExecutionResult {
             outcome,
+            status_code: None,
             duration: Duration::from_nanos(duration_nanos),
+            time_to_headers: None,
+            scheduled: now,
+            started: now,
+            phases: Phases::default(),
+            request_bytes: 0,
+            response_bytes: 0,
+            failed_assertions: Vec::new(),
+            failure: None,
         }
     }
 
     #[test]
     fn statistics_add_success() {
-        let mut s = Statistics::new(10);
+        let mut s = Statistics::default();
         s.add(&result(HttpRequestOutcome::SuccessResponse, 100));
         s.add(&result(HttpRequestOutcome::SuccessResponse, 200));
         assert_eq!(s.successful_response, 2);
@@ -124,7 +371,7 @@ mod tests {
 
     #[test]
     fn statistics_add_mixed_outcomes() {
-        let mut s = Statistics::new(10);
+        let mut s = Statistics::default();
         s.add(&result(HttpRequestOutcome::SuccessResponse, 50));
         s.add(&result(HttpRequestOutcome::ClientErrorResponse, 100));
         s.add(&result(HttpRequestOutcome::Timeout, 200));
@@ -134,4 +381,228 @@ mod tests {
         assert_eq!(s.success_latencies.len(), 1);
         assert_eq!(s.non_success_latencies.len(), 2);
     }
+
+    #[test]
+    fn statistics_add_transport_errors() {
+        let mut s = Statistics::default();
+        for outcome in [
+            HttpRequestOutcome::ConnectError,
+            HttpRequestOutcome::ConnectError,
+            HttpRequestOutcome::DnsError,
+            HttpRequestOutcome::TlsError,
+            HttpRequestOutcome::ConnectionReset,
+            HttpRequestOutcome::BodyError,
+        ] {
+            s.add(&result(outcome, 10));
+        }
+        assert_eq!(s.connect_errors, 2);
+        assert_eq!(s.dns_errors, 1);
+        assert_eq!(s.tls_errors, 1);
+        assert_eq!(s.connection_resets, 1);
+        assert_eq!(s.body_errors, 1);
+        assert_eq!(s.non_success_latencies.len(), 6);
+        assert!(s.success_latencies.is_empty());
+    }
+
+    #[test]
+    fn statistics_counts_exact_status_codes() {
+        let mut s = Statistics::default();
+        for status_code in [429, 404, 429, 503, 500] {
+            s.add(&ExecutionResult {
+                status_code: Some(status_code),
+                ..result(HttpRequestOutcome::ClientErrorResponse, 10)
+            });
+        }
+        s.add_warmup(&ExecutionResult {
+            status_code: Some(200),
+            ..result(HttpRequestOutcome::SuccessResponse, 10)
+        });
+        // No response, no status code.
+        s.add(&result(HttpRequestOutcome::ConnectError, 10));
+        assert_eq!(
+            s.status_codes,
+            BTreeMap::from([(200, 1), (404, 1), (429, 2), (500, 1), (503, 1)])
+        );
+    }
+
+    #[test]
+    fn statistics_add_records_response_time_from_schedule() {
+        let mut s = Statistics::default();
+        let scheduled = Instant::now();
+        s.add(&ExecutionResult {
+            outcome: HttpRequestOutcome::SuccessResponse,
+            status_code: Some(200),
+            duration: Duration::from_millis(10),
+            time_to_headers: Some(Duration::from_millis(4)),
+            scheduled,
+            started: scheduled + Duration::from_millis(90),
+            phases: Phases::default(),
+            request_bytes: 0,
+            response_bytes: 0,
+            failed_assertions: Vec::new(),
+            failure: None,
+        });
+        assert_eq!(s.success_latencies, histogram(&[Duration::from_millis(10)]));
+        assert_eq!(s.response_times, histogram(&[Duration::from_millis(100)]));
+        assert_eq!(s.time_to_headers, histogram(&[Duration::from_millis(4)]));
+        assert_eq!(s.full_response, histogram(&[Duration::from_millis(10)]));
+        assert_eq!(s.window, histogram(&[Duration::from_millis(10)]));
+    }
+
+    #[test]
+    fn statistics_add_separates_time_to_headers_from_full_response() {
+        let mut s = Statistics::default();
+        let ms = Duration::from_millis;
+        s.add(&ExecutionResult {
+            status_code: Some(200),
+            time_to_headers: Some(ms(5)),
+            ..result(HttpRequestOutcome::SuccessResponse, 50_000_000)
+        });
+        // Headers, but the body broke off.
+        s.add(&ExecutionResult {
+            time_to_headers: Some(ms(6)),
+            ..result(HttpRequestOutcome::BodyError, 60_000_000)
+        });
+        // No response at all.
+        s.add(&result(HttpRequestOutcome::ConnectError, 70_000_000));
+        assert_eq!(s.time_to_headers, histogram(&[ms(5), ms(6)]));
+        assert_eq!(s.full_response, histogram(&[ms(50)]));
+    }
+
+    #[test]
+    fn statistics_counts_assertion_failures() {
+        let mut s = Statistics::default();
+        let failed = |failed_assertions: Vec<usize>| ExecutionResult {
+            status_code: Some(200),
+            failed_assertions,
+            ..result(HttpRequestOutcome::AssertionFailed, 10)
+        };
+        s.add(&failed(vec![0]));
+        s.add(&failed(vec![0, 2]));
+        s.add_warmup(&failed(vec![2]));
+        assert_eq!(s.assertion_failed, 3);
+        assert_eq!(s.assertion_failures, BTreeMap::from([(0, 2), (2, 2)]));
+        assert_eq!(s.non_success_latencies.len(), 2);
+    }
+
+    #[test]
+    fn statistics_samples_failed_requests() {
+        let mut s = Statistics::default();
+        let failed = ExecutionResult {
+            failure: Some(FailureDetail {
+                at: SystemTime::UNIX_EPOCH,
+                headers: http::HeaderMap::new(),
+                body: Vec::new(),
+                body_truncated: false,
+                error: Some("connection refused".to_owned()),
+            }),
+            ..result(HttpRequestOutcome::ConnectError, 10)
+        };
+        s.add(&result(HttpRequestOutcome::SuccessResponse, 10));
+        s.add(&failed);
+        s.add_warmup(&failed);
+        assert_eq!(s.failures.into_items(), vec![failed.clone(), failed]);
+    }
+
+    #[test]
+    fn statistics_add_counts_bytes_and_response_sizes() {
+        let mut s = Statistics::default();
+        s.add(&ExecutionResult {
+            status_code: Some(200),
+            request_bytes: 100,
+            response_bytes: 4_096,
+            ..result(HttpRequestOutcome::SuccessResponse, 10)
+        });
+        // A body that broke off counts towards the bytes, but not the sizes of full responses.
+        s.add(&ExecutionResult {
+            request_bytes: 100,
+            response_bytes: 1_000,
+            ..result(HttpRequestOutcome::BodyError, 10)
+        });
+        s.add_warmup(&ExecutionResult {
+            status_code: Some(200),
+            request_bytes: 100,
+            response_bytes: 4_096,
+            ..result(HttpRequestOutcome::SuccessResponse, 10)
+        });
+        assert_eq!(s.request_bytes, 300);
+        assert_eq!(s.response_bytes, 9_192);
+        assert_eq!(s.response_sizes, sizes(&[4_096]));
+    }
+
+    #[test]
+    fn statistics_add_collects_phase_times() {
+        let mut s = Statistics::default();
+        let ms = Duration::from_millis;
+        // A request on a new connection, then one on a pooled connection.
+        s.add(&ExecutionResult {
+            phases: Phases {
+                dns: Some(ms(1)),
+                connect: Some(ms(2)),
+                tls: Some(ms(8)),
+                ttfb: Some(ms(3)),
+                body: Some(ms(4)),
+            },
+            ..result(HttpRequestOutcome::SuccessResponse, 10)
+        });
+        s.add(&ExecutionResult {
+            phases: Phases {
+                ttfb: Some(ms(5)),
+                body: Some(ms(6)),
+                ..Phases::default()
+            },
+            ..result(HttpRequestOutcome::SuccessResponse, 11)
+        });
+        s.add_warmup(&ExecutionResult {
+            phases: Phases {
+                ttfb: Some(ms(7)),
+                ..Phases::default()
+            },
+            ..result(HttpRequestOutcome::SuccessResponse, 12)
+        });
+        assert_eq!(
+            s.phase_times,
+            PhaseTimes {
+                dns: histogram(&[ms(1)]),
+                connect: histogram(&[ms(2)]),
+                tls: histogram(&[ms(8)]),
+                ttfb: histogram(&[ms(3), ms(5)]),
+                body: histogram(&[ms(4), ms(6)]),
+            }
+        );
+    }
+
+    #[test]
+    fn statistics_add_dropped_counts_without_latency() {
+        let mut s = Statistics::default();
+        s.add_dropped();
+        s.add_dropped();
+        assert_eq!(s.dropped, 2);
+        assert!(s.success_latencies.is_empty());
+        assert!(s.non_success_latencies.is_empty());
+        assert!(s.response_times.is_empty());
+    }
+
+    #[test]
+    fn statistics_add_abandoned_accumulates() {
+        let mut s = Statistics::default();
+        s.add_abandoned(3);
+        s.add_abandoned(0);
+        s.add_abandoned(2);
+        assert_eq!(s.abandoned, 5);
+        assert!(s.success_latencies.is_empty());
+    }
+
+    #[test]
+    fn statistics_add_warmup_counts_without_latency() {
+        let mut s = Statistics::default();
+        s.add_warmup(&result(HttpRequestOutcome::SuccessResponse, 100));
+        s.add_warmup(&result(HttpRequestOutcome::ServerErrorResponse, 100));
+        assert_eq!(s.successful_response, 1);
+        assert_eq!(s.server_error_response, 1);
+        assert!(s.success_latencies.is_empty());
+        assert!(s.non_success_latencies.is_empty());
+        assert!(s.response_times.is_empty());
+        assert!(s.window.is_empty());
+    }
 }
@@ -0,0 +1,166 @@
+//! Latency percentiles over time.
+//!
+//! Every --snapshot-interval, each worker hands the latencies of the requests it completed since its
+//! previous snapshot to the timeline task. Once every worker still running has reported a window,
+//! the task merges their histograms into one point (p50, p90, p99, max) and drops them, so memory
+//! grows with the number of points rather than with the number of requests.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::histogram::Histogram;
+
+/// Latencies one worker completed during its snapshot window `index` (its `index`-th snapshot).
+/// Workers start together and snapshot on the same interval, so their windows of the same index
+/// cover the same stretch of the run.
+#[derive(Debug)]
+pub(crate) struct Window {
+    pub worker: usize,
+    pub index: usize,
+    /// Time from the start of the run to the snapshot.
+    pub elapsed: Duration,
+    pub latencies: Histogram,
+    /// The worker's final window: it reports no more after this one.
+    pub last: bool,
+}
+
+/// Latency percentiles of the requests completed during one snapshot window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LatencyPoint {
+    /// Time from the start of the run to the end of the window (its last worker's snapshot).
+    pub elapsed: Duration,
+    pub requests: u64,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// Collects the windows of `workers` workers until they all hang up, and returns one point per
+/// window in which requests completed, in time order.
+pub(crate) async fn collect(
+    workers: usize,
+    mut windows: UnboundedReceiver<Window>,
+) -> Vec<LatencyPoint> {
+    let mut pending: BTreeMap<usize, (Duration, Histogram)> = BTreeMap::new();
+    // Next window each worker will report, or `None` once it has reported its last one.
+    let mut next: Vec<Option<usize>> = vec![Some(0); workers];
+    let mut points = Vec::new();
+    while let Some(window) = windows.recv().await {
+        if let Some(next) = next.get_mut(window.worker) {
+            *next = (!window.last).then(|| window.index.saturating_add(1));
+        }
+        match pending.get_mut(&window.index) {
+            Some(&mut (ref mut elapsed, ref mut latencies)) => {
+                *elapsed = (*elapsed).max(window.elapsed);
+                latencies.merge(&window.latencies);
+            }
+            None => {
+                pending.insert(window.index, (window.elapsed, window.latencies));
+            }
+        }
+        // Windows before this one have been reported by every worker still running.
+        let complete = next.iter().flatten().min().copied().unwrap_or(usize::MAX);
+        while let Some(entry) = pending.first_entry()
+            && *entry.key() < complete
+        {
+            let (elapsed, latencies) = entry.remove();
+            points.extend(point(elapsed, &latencies));
+        }
+    }
+    // Workers that failed never reported their last window; close whatever they left.
+    points.extend(
+        pending
+            .values()
+            .filter_map(|&(elapsed, ref latencies)| point(elapsed, latencies)),
+    );
+    points
+}
+
+/// The point of a window ending at `elapsed`, or `None` if no request completed during it.
+fn point(elapsed: Duration, latencies: &Histogram) -> Option<LatencyPoint> {
+    if latencies.is_empty() {
+        return None;
+    }
+    Some(LatencyPoint {
+        elapsed,
+        requests: latencies.len(),
+        p50: Duration::from_nanos(latencies.quantile(0.5)),
+        p90: Duration::from_nanos(latencies.quantile(0.9)),
+        p99: Duration::from_nanos(latencies.quantile(0.99)),
+        max: Duration::from_nanos(latencies.max()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    /// Window `index` of `worker`, snapshot `index` seconds into the run.
+    fn window(worker: usize, index: usize, millis: &[u64], last: bool) -> Window {
+        let mut latencies = Histogram::default();
+        for &ms in millis {
+            latencies.record_duration(Duration::from_millis(ms));
+        }
+        Window {
+            worker,
+            index,
+            elapsed: Duration::from_secs(u64::try_from(index).unwrap()),
+            latencies,
+            last,
+        }
+    }
+
+    #[tokio::test]
+    async fn merges_the_workers_windows() {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let collector = tokio::spawn(collect(2, receiver));
+        for sent in [
+            window(0, 0, &[], false),
+            window(1, 0, &[], false),
+            window(0, 1, &[10, 20], false),
+            window(1, 1, &[30], false),
+            // Nothing completed in window 2.
+            window(1, 2, &[], false),
+            window(0, 2, &[], false),
+            // The final snapshots come after the drain, a little later than the last tick.
+            Window {
+                elapsed: Duration::from_millis(3_400),
+                ..window(0, 3, &[100], true)
+            },
+            window(1, 3, &[], true),
+        ] {
+            sender.send(sent).unwrap();
+        }
+        drop(sender);
+        let points = collector.await.unwrap();
+        let summary: Vec<(Duration, u64)> =
+            points.iter().map(|p| (p.elapsed, p.requests)).collect();
+        assert_eq!(
+            summary,
+            [
+                (Duration::from_secs(1), 3),
+                (Duration::from_millis(3_400), 1)
+            ]
+        );
+        let first = points[0];
+        assert!(first.p50 >= Duration::from_millis(20) && first.p50 < Duration::from_millis(21));
+        assert!(first.max >= Duration::from_millis(30) && first.max < Duration::from_millis(31));
+    }
+
+    #[tokio::test]
+    async fn windows_of_a_worker_that_never_finished_are_still_closed() {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let collector = tokio::spawn(collect(2, receiver));
+        sender.send(window(0, 0, &[5], false)).unwrap();
+        sender.send(window(1, 0, &[5], false)).unwrap();
+        sender.send(window(1, 1, &[7], true)).unwrap();
+        drop(sender);
+        let points = collector.await.unwrap();
+        let summary: Vec<(Duration, u64)> =
+            points.iter().map(|p| (p.elapsed, p.requests)).collect();
+        assert_eq!(summary, [(Duration::ZERO, 2), (Duration::from_secs(1), 1)]);
+    }
+}
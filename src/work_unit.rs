@@ -1,26 +1,43 @@
 use futures::StreamExt as _;
 
 use crate::{
+    assertion::{self, BodyCheck},
     cli::{Args, HttpProtocol, Payload},
     error::{AppError, Result},
+    phases::{self, ResolveError, TimingLayer, TimingResolver},
 };
+use http::HeaderMap;
 use std::{
     future::Future,
-    time::{Duration, Instant},
+    io::ErrorKind,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
 };
 
+/// Bytes of the response body kept for the failure sample.
+pub const SAMPLE_BODY_LIMIT: usize = 1024;
+
 pub trait WorkUnit {
-    fn execute(&self) -> impl Future<Output = Result<ExecutionResult>> + Send + '_;
+    /// Executes one unit of work that the scheduler intended to start at `scheduled`.
+    fn execute(
+        &self,
+        scheduled: Instant,
+    ) -> impl Future<Output = Result<ExecutionResult>> + Send + '_;
 }
 
 pub struct RequestWorkUnit {
     args: Args,
     client: reqwest::Client,
+    /// Size of the request as sent, see [`request_size`].
+    request_bytes: u64,
 }
 
 impl WorkUnit for RequestWorkUnit {
-    fn execute(&self) -> impl Future<Output = Result<ExecutionResult>> + Send + '_ {
-        self.do_execute()
+    fn execute(
+        &self,
+        scheduled: Instant,
+    ) -> impl Future<Output = Result<ExecutionResult>> + Send + '_ {
+        self.do_execute(scheduled)
     }
 }
 
@@ -77,6 +94,11 @@ impl RequestWorkUnit {
         // Use rustls
         builder = builder.tls_backend_rustls();
 
+        // Time name resolution and connection setup for the phase breakdown (the TLS handshake is
+        // timed through the crypto provider main installs, see `phases::time_tls`).
+        builder = builder.dns_resolver(Arc::new(TimingResolver));
+        builder = builder.connector_layer(TimingLayer);
+
         // TODO:
         // builder = builder.http2_adaptive_window(true);
         let client = builder
@@ -86,17 +108,70 @@ impl RequestWorkUnit {
         Ok(Self {
             args: args.clone(),
             client,
+            request_bytes: request_size(args),
         })
     }
 
-    async fn do_execute(&self) -> Result<ExecutionResult> {
-        let start = Instant::now();
-        let outcome = self.do_execute_inner().await?;
-        let duration = start.elapsed();
-        Ok(ExecutionResult { outcome, duration })
+    async fn do_execute(&self, scheduled: Instant) -> Result<ExecutionResult> {
+        let started = Instant::now();
+        let (completion, connection) = phases::track(self.do_execute_inner()).await;
+        let Completion {
+            outcome,
+            status_code,
+            headers,
+            response_headers,
+            body,
+            response_bytes,
+            failed_assertions,
+            error,
+            sent,
+        } = completion?;
+        let ended = Instant::now();
+        let duration = ended.saturating_duration_since(started);
+
+        let failure = (outcome != HttpRequestOutcome::SuccessResponse).then(|| {
+            let body_truncated =
+                response_bytes > u64::try_from(SAMPLE_BODY_LIMIT).expect("limit fits in u64");
+            FailureDetail {
+                at: SystemTime::now(),
+                headers: response_headers,
+                body,
+                body_truncated,
+                error,
+            }
+        });
+
+        let setup = connection.setup.unwrap_or_default();
+        let phases = Phases {
+            dns: connection.dns,
+            connect: connection.setup.map(|setup| {
+                setup
+                    .saturating_sub(connection.dns.unwrap_or_default())
+                    .saturating_sub(connection.tls.unwrap_or_default())
+            }),
+            tls: connection.tls,
+            ttfb: headers.map(|at| at.saturating_duration_since(started).saturating_sub(setup)),
+            body: headers
+                .filter(|_| is_full_response(outcome))
+                .map(|at| ended.saturating_duration_since(at)),
+        };
+        Ok(ExecutionResult {
+            outcome,
+            status_code,
+            duration,
+            time_to_headers: headers.map(|at| at.saturating_duration_since(started)),
+            scheduled,
+            started,
+            phases,
+            request_bytes: if sent { self.request_bytes } else { 0 },
+            response_bytes,
+            failed_assertions,
+            failure,
+        })
     }
 
-    async fn do_execute_inner(&self) -> Result<HttpRequestOutcome> {
+    /// Sends the request and reads the response.
+    async fn do_execute_inner(&self) -> Result<Completion> {
         let url = self.args.url.clone();
         let method = self.args.request.clone();
 
@@ -111,51 +186,363 @@ impl RequestWorkUnit {
             None => {}
         }
 
-        let result = request_builder.send().await;
-
-        if let Err(ref source) = result
-            && source.is_timeout()
-        {
-            return Ok(HttpRequestOutcome::Timeout);
-        }
-
-        let response = result.map_err(|source| AppError::FailedToSendRequest { source })?;
+        let response = match request_builder.send().await {
+            Ok(response) => response,
+            Err(source) => {
+                return transport_outcome(&source)
+                    .map(|outcome| Completion {
+                        outcome,
+                        status_code: None,
+                        headers: None,
+                        response_headers: HeaderMap::new(),
+                        body: Vec::new(),
+                        response_bytes: 0,
+                        failed_assertions: Vec::new(),
+                        error: Some(error_chain(&source)),
+                        // Failing to connect (DNS, TCP or TLS) means nothing went out.
+                        sent: !source.is_connect(),
+                    })
+                    .ok_or(AppError::FailedToSendRequest { source });
+            }
+        };
+        let headers = Instant::now();
 
         let status = response.status();
-        let _headers = response.headers().clone();
+        let response_headers = response.headers().clone();
+        // Only the start of the body is kept, for the failure sample; assertions see it as it streams.
+        let mut body = Vec::new();
+        let mut body_check = BodyCheck::new(&self.args.assertions, self.args.expect_body_max_bytes);
 
+        let mut response_bytes: u64 = 0;
         let mut bytes_stream = response.bytes_stream();
         while let Some(result) = bytes_stream.next().await {
-            if let Err(ref source) = result
-                && source.is_timeout()
-            {
-                return Ok(HttpRequestOutcome::Timeout);
+            match result {
+                Ok(chunk) => {
+                    response_bytes = response_bytes.saturating_add(
+                        u64::try_from(chunk.len()).expect("chunk size fits in u64"),
+                    );
+                    body_check.feed(&chunk);
+                    let room = SAMPLE_BODY_LIMIT.saturating_sub(body.len());
+                    body.extend(chunk.iter().take(room));
+                }
+                Err(source) => {
+                    // The response started but did not arrive in full.
+                    let outcome = if source.is_timeout() {
+                        HttpRequestOutcome::Timeout
+                    } else {
+                        HttpRequestOutcome::BodyError
+                    };
+                    return Ok(Completion {
+                        outcome,
+                        status_code: Some(status.as_u16()),
+                        headers: Some(headers),
+                        response_headers,
+                        body,
+                        response_bytes,
+                        failed_assertions: Vec::new(),
+                        error: Some(error_chain(&source)),
+                        sent: true,
+                    });
+                }
             }
-
-            let _chunk = result.map_err(|source| AppError::FailedToGetResponseChunk { source })?;
-            // println!("chunk size : {:?}", chunk.len());
         }
 
-        if status.is_informational() {
-            Ok(HttpRequestOutcome::InformationalResponse)
-        } else if status.is_redirection() {
-            Ok(HttpRequestOutcome::RedirectionMessage)
-        } else if status.is_success() {
-            Ok(HttpRequestOutcome::SuccessResponse)
-        } else if status.is_client_error() {
-            Ok(HttpRequestOutcome::ClientErrorResponse)
-        } else if status.is_server_error() {
-            Ok(HttpRequestOutcome::ServerErrorResponse)
+        let failed_assertions = assertion::failures(
+            &self.args.assertions,
+            status,
+            &response_headers,
+            &body_check,
+        );
+        let outcome = response_outcome(status, &failed_assertions);
+        let error = (!failed_assertions.is_empty())
+            .then(|| self.assertion_error(&failed_assertions, &body_check));
+        Ok(Completion {
+            outcome,
+            status_code: Some(status.as_u16()),
+            headers: Some(headers),
+            response_headers,
+            body,
+            response_bytes,
+            failed_assertions,
+            error,
+            sent: true,
+        })
+    }
+
+    /// The error recorded for a response that failed the assertions at `failed`.
+    fn assertion_error(&self, failed: &[usize], body_check: &BodyCheck) -> String {
+        let failed: Vec<String> = failed
+            .iter()
+            .filter_map(|&index| self.args.assertions.get(index))
+            .map(ToString::to_string)
+            .collect();
+        let note = if body_check.over_limit() {
+            " (body longer than --expect-body-max-bytes)"
         } else {
-            Ok(HttpRequestOutcome::OtherError)
+            ""
+        };
+        format!("failed assertions: {}{note}", failed.join("; "))
+    }
+}
+
+/// How a request ended, as seen from inside `do_execute`.
+struct Completion {
+    outcome: HttpRequestOutcome,
+    /// HTTP status of the response; `None` unless its headers arrived.
+    status_code: Option<u16>,
+    /// When the response headers arrived, if they did.
+    headers: Option<Instant>,
+    /// The response headers; empty if they never arrived.
+    response_headers: HeaderMap,
+    /// The start of the body received, at most [`SAMPLE_BODY_LIMIT`] bytes.
+    body: Vec<u8>,
+    /// Response body bytes received, in full or until the body broke off.
+    response_bytes: u64,
+    /// Indexes of the assertions a full response failed.
+    failed_assertions: Vec<usize>,
+    /// What went wrong, when the status code does not say: the transport error or the failed
+    /// assertions.
+    error: Option<String>,
+    /// Whether the request reached a connection, so counts as sent.
+    sent: bool,
+}
+
+/// Size of the request as an HTTP/1.1 message: request line, headers and body. The headers are
+/// the --header values plus those the client adds when they are missing (`host`, `accept` and
+/// `content-length`). Over HTTP/2 the headers are compressed, so fewer bytes go out.
+fn request_size(args: &Args) -> u64 {
+    let body = match args.payload {
+        Some(Payload::Data(ref data) | Payload::File(ref data)) => data.len(),
+        None => 0,
+    };
+    let target = &args.url[url::Position::BeforePath..url::Position::AfterQuery];
+    let host = &args.url[url::Position::BeforeHost..url::Position::AfterPort];
+    let length = body.to_string();
+    let added = [
+        (http::header::HOST, host),
+        (http::header::ACCEPT, "*/*"),
+        (http::header::CONTENT_LENGTH, length.as_str()),
+    ];
+    let added = added
+        .iter()
+        .filter(|entry| entry.0 != http::header::CONTENT_LENGTH || args.payload.is_some())
+        .filter(|entry| !args.header.contains_key(&entry.0))
+        .map(|entry| (entry.0.as_str().len(), entry.1.len()));
+    let given = args
+        .header
+        .iter()
+        .map(|(name, value)| (name.as_str().len(), value.len()));
+    let mut size = format!("{} {target} HTTP/1.1\r\n", args.request).len();
+    // "name: value\r\n" per header, then the blank line ending them.
+    for (name, value) in added.chain(given) {
+        size = size
+            .saturating_add(name)
+            .saturating_add(value)
+            .saturating_add(4);
+    }
+    size = size.saturating_add(2).saturating_add(body);
+    u64::try_from(size).expect("request size fits in u64")
+}
+
+/// Outcome of a full response that failed the assertions at `failed_assertions`. Only a response
+/// that would have been a success becomes an assertion failure; others keep their status class.
+fn response_outcome(status: http::StatusCode, failed_assertions: &[usize]) -> HttpRequestOutcome {
+    let outcome = status_outcome(status);
+    if outcome == HttpRequestOutcome::SuccessResponse && !failed_assertions.is_empty() {
+        HttpRequestOutcome::AssertionFailed
+    } else {
+        outcome
+    }
+}
+
+/// Outcome of a full response, going by its status code.
+fn status_outcome(status: http::StatusCode) -> HttpRequestOutcome {
+    if status.is_informational() {
+        HttpRequestOutcome::InformationalResponse
+    } else if status.is_redirection() {
+        HttpRequestOutcome::RedirectionMessage
+    } else if status.is_success() {
+        HttpRequestOutcome::SuccessResponse
+    } else if status.is_client_error() {
+        HttpRequestOutcome::ClientErrorResponse
+    } else if status.is_server_error() {
+        HttpRequestOutcome::ServerErrorResponse
+    } else {
+        HttpRequestOutcome::OtherError
+    }
+}
+
+/// The error followed by its causes, e.g. "error sending request for url (...): client error
+/// (Connect): tcp connect error: Connection refused (os error 111)".
+fn error_chain(error: &reqwest::Error) -> String {
+    let mut text = error.to_string();
+    let mut cause = std::error::Error::source(error);
+    while let Some(current) = cause {
+        text.push_str(": ");
+        text.push_str(&current.to_string());
+        cause = current.source();
+    }
+    text
+}
+
+/// Outcome of a request that failed before its response arrived, or `None` if the failure is not a
+/// transport one (e.g. an invalid request or too many redirects), which stops the run.
+fn transport_outcome(error: &reqwest::Error) -> Option<HttpRequestOutcome> {
+    if error.is_timeout() {
+        return Some(HttpRequestOutcome::Timeout);
+    }
+    if error.is_builder() || error.is_redirect() {
+        return None;
+    }
+    let mut connect = error.is_connect();
+    let mut cause = std::error::Error::source(error);
+    while let Some(current) = cause {
+        if current.is::<ResolveError>() {
+            return Some(HttpRequestOutcome::DnsError);
+        }
+        if current.is::<rustls::Error>() {
+            return Some(HttpRequestOutcome::TlsError);
         }
+        if let Some(outcome) = current
+            .downcast_ref::<std::io::Error>()
+            .and_then(io_outcome)
+        {
+            return Some(outcome);
+        }
+        if let Some(hyper) = current.downcast_ref::<hyper::Error>()
+            && (hyper.is_incomplete_message() || hyper.is_canceled() || hyper.is_closed())
+        {
+            // e.g. the server closed a kept-alive connection before answering.
+            return Some(HttpRequestOutcome::ConnectionReset);
+        }
+        connect |= current
+            .downcast_ref::<hyper_util::client::legacy::Error>()
+            .is_some_and(hyper_util::client::legacy::Error::is_connect);
+        cause = current.source();
+    }
+    if connect {
+        // Any other failure to connect, e.g. a proxy refusing the tunnel.
+        Some(HttpRequestOutcome::ConnectError)
+    } else if error.is_body() || error.is_decode() {
+        Some(HttpRequestOutcome::BodyError)
+    } else {
+        None
     }
 }
 
+/// Outcome for an I/O error kind that identifies a transport failure, looking through I/O errors
+/// wrapped in it (which `source()` skips).
+fn io_outcome(error: &std::io::Error) -> Option<HttpRequestOutcome> {
+    let kind = error.kind();
+    if matches!(
+        kind,
+        ErrorKind::ConnectionRefused
+            | ErrorKind::NotConnected
+            | ErrorKind::AddrNotAvailable
+            | ErrorKind::HostUnreachable
+            | ErrorKind::NetworkUnreachable
+    ) {
+        return Some(HttpRequestOutcome::ConnectError);
+    }
+    if matches!(
+        kind,
+        ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+            | ErrorKind::UnexpectedEof
+    ) {
+        return Some(HttpRequestOutcome::ConnectionReset);
+    }
+    let inner = error.get_ref()?;
+    // tokio-rustls wraps handshake and certificate failures in an I/O error.
+    if inner.is::<rustls::Error>() {
+        return Some(HttpRequestOutcome::TlsError);
+    }
+    inner.downcast_ref::<std::io::Error>().and_then(io_outcome)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ExecutionResult {
     pub outcome: HttpRequestOutcome,
+    /// HTTP status of the response, even if its body then broke off; `None` unless its headers
+    /// arrived.
+    pub status_code: Option<u16>,
+    /// Service time: from the moment the request was actually sent until the response ended.
     pub duration: Duration,
+    /// From the moment the request was actually sent until the response headers arrived; `None` if
+    /// they never did.
+    pub time_to_headers: Option<Duration>,
+    /// When the scheduler intended to send the request.
+    pub scheduled: Instant,
+    /// When the request was actually sent (at or after `scheduled`).
+    pub started: Instant,
+    /// Breakdown of the service time by phase.
+    pub phases: Phases,
+    /// Request bytes sent, counted as an HTTP/1.1 message (request line, headers and body) whatever
+    /// the protocol: over HTTP/2, an estimate. 0 if no connection was made.
+    pub request_bytes: u64,
+    /// Response body bytes received, as sent on the wire (responses are not decompressed).
+    pub response_bytes: u64,
+    /// Indexes into [`Args::assertions`] of the assertions the response failed.
+    pub failed_assertions: Vec<usize>,
+    /// What the failure looked like; `None` for a successful request.
+    pub failure: Option<FailureDetail>,
+}
+
+/// Whether a request that got its response headers and ended with `outcome` also got the whole
+/// body.
+fn is_full_response(outcome: HttpRequestOutcome) -> bool {
+    !matches!(
+        outcome,
+        HttpRequestOutcome::BodyError | HttpRequestOutcome::Timeout
+    )
+}
+
+/// What is kept of a failed request for the failure sample in the report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailureDetail {
+    /// When the request ended.
+    pub at: SystemTime,
+    /// The response headers; empty if they never arrived.
+    pub headers: HeaderMap,
+    /// The start of the response body, at most [`SAMPLE_BODY_LIMIT`] bytes.
+    pub body: Vec<u8>,
+    /// Whether the body was longer than what was kept.
+    pub body_truncated: bool,
+    /// What went wrong, when the status code does not say: the transport error or the failed
+    /// assertions.
+    pub error: Option<String>,
+}
+
+/// Time spent in each phase of a request. A phase the request did not go through is `None`: the
+/// connection phases when it reused a pooled connection, and any phase after the one it failed in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Phases {
+    /// Name resolution.
+    pub dns: Option<Duration>,
+    /// TCP connect, after name resolution.
+    pub connect: Option<Duration>,
+    /// TLS handshake, for HTTPS.
+    pub tls: Option<Duration>,
+    /// From the connection being ready until the response headers arrived.
+    pub ttfb: Option<Duration>,
+    /// From the response headers until the end of the body.
+    pub body: Option<Duration>,
+}
+
+impl ExecutionResult {
+    /// Response time: from the intended send time until the response ended. Unlike the service
+    /// time, it includes any delay in sending, so a stall is not hidden by coordinated omission.
+    pub fn response_time(&self) -> Duration {
+        self.started
+            .saturating_duration_since(self.scheduled)
+            .saturating_add(self.duration)
+    }
+
+    /// Whether the whole response arrived, body included.
+    pub fn is_full_response(&self) -> bool {
+        self.status_code.is_some() && is_full_response(self.outcome)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -167,4 +554,235 @@ pub enum HttpRequestOutcome {
     ServerErrorResponse,
     OtherError,
     Timeout,
+    /// The TCP connection could not be established (e.g. refused or unreachable).
+    ConnectError,
+    /// The host name did not resolve.
+    DnsError,
+    /// The TLS handshake failed (e.g. an untrusted certificate).
+    TlsError,
+    /// The connection was reset or closed before a response arrived.
+    ConnectionReset,
+    /// The response started but its body failed to arrive in full.
+    BodyError,
+    /// A 2xx response failed at least one --expect-* assertion.
+    AssertionFailed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt as _;
+    use tokio::net::TcpListener;
+
+    /// Outcome of a plain GET to `url`, which must fail.
+    async fn failed_get(url: &str) -> Option<HttpRequestOutcome> {
+        failed_get_with(reqwest::Client::builder(), url).await
+    }
+
+    /// Outcome of a plain GET to `url` by a client from `builder`, which must fail.
+    async fn failed_get_with(
+        builder: reqwest::ClientBuilder,
+        url: &str,
+    ) -> Option<HttpRequestOutcome> {
+        let client = builder.tls_backend_rustls().build().unwrap();
+        let error = client.get(url).send().await.unwrap_err();
+        transport_outcome(&error)
+    }
+
+    /// Resolver that knows no host, failing like the system one does for an unknown name.
+    struct NoSuchHost;
+
+    impl reqwest::dns::Resolve for NoSuchHost {
+        fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+            let error = ResolveError {
+                name: name.as_str().to_owned(),
+                source: std::io::Error::new(ErrorKind::NotFound, "no such host"),
+            };
+            Box::pin(async move { Err(error.into()) })
+        }
+    }
+
+    /// Local server that answers every connection with `reply`, then closes it.
+    async fn serve(reply: &'static [u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                stream.write_all(reply).await.unwrap_or_default();
+            }
+        });
+        addr.to_string()
+    }
+
+    fn minimal_args(url: &str) -> Args {
+        Args {
+            url: url::Url::parse(url).unwrap(),
+            header: HeaderMap::new(),
+            insecure: false,
+            request: http::Method::GET,
+            cacert: None,
+            cert: None,
+            key: None,
+            location: false,
+            requests_per_second: rust_decimal::Decimal::from(1),
+            concurrency: None,
+            think_time: None,
+            ramp_up: None,
+            ramp_down: None,
+            stages: None,
+            arrival: crate::cli::ArrivalProcess::Constant,
+            seed: 0,
+            max_in_flight: None,
+            workers: None,
+            threads: None,
+            warmup: None,
+            requests: None,
+            search: None,
+            adaptive: None,
+            abort: None,
+            duration: Duration::from_secs(10),
+            drain_timeout: None,
+            assertions: Vec::new(),
+            expect_body_max_bytes: 1_048_576,
+            max_time: None,
+            connect_timeout: None,
+            output: std::path::PathBuf::from("report.pb"),
+            events: None,
+            histogram_precision: 3,
+            snapshot_interval: Duration::from_millis(250),
+            protocol: HttpProtocol::Http1_1,
+            payload: None,
+            identity: None,
+            root_certificates: None,
+            dry_run: true,
+            upload_file_path: None,
+        }
+    }
+
+    /// Local server that reads one request with a `body`-byte body, answers it with a 200 and
+    /// returns how many bytes the request took.
+    async fn capture(body: usize) -> (String, tokio::task::JoinHandle<usize>) {
+        use tokio::io::AsyncReadExt as _;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let received = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0_u8; 1024];
+            loop {
+                let end = memchr::memmem::find(&request, b"\r\n\r\n");
+                if end.is_some_and(|end| request.len() >= end + 4 + body) {
+                    break;
+                }
+                let read = stream.read(&mut buf).await.unwrap();
+                assert_ne!(read, 0, "request ended early");
+                request.extend_from_slice(&buf[..read]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            request.len()
+        });
+        (addr.to_string(), received)
+    }
+
+    #[tokio::test]
+    async fn request_bytes_count_the_whole_request() {
+        let (addr, received) = capture(5).await;
+        let mut args = minimal_args(&format!("http://{addr}/submit?id=7"));
+        args.request = http::Method::POST;
+        args.payload = Some(Payload::Data(bytes::Bytes::from_static(b"hello")));
+        args.header
+            .insert("x-test", http::HeaderValue::from_static("yes"));
+        let unit = RequestWorkUnit::new(&args).unwrap();
+        let result = unit.execute(Instant::now()).await.unwrap();
+        assert_eq!(result.outcome, HttpRequestOutcome::SuccessResponse);
+        let received = u64::try_from(received.await.unwrap()).unwrap();
+        assert_eq!(result.request_bytes, received);
+    }
+
+    #[tokio::test]
+    async fn request_bytes_count_requests_sent_without_a_response() {
+        let addr = serve(b"").await;
+        let unit = RequestWorkUnit::new(&minimal_args(&format!("http://{addr}/"))).unwrap();
+        let result = unit.execute(Instant::now()).await.unwrap();
+        assert_eq!(result.outcome, HttpRequestOutcome::ConnectionReset);
+        assert!(result.request_bytes > 0);
+
+        let addr = {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap()
+        };
+        let unit = RequestWorkUnit::new(&minimal_args(&format!("http://{addr}/"))).unwrap();
+        let result = unit.execute(Instant::now()).await.unwrap();
+        assert_eq!(result.outcome, HttpRequestOutcome::ConnectError);
+        assert_eq!(result.request_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn body_breaking_off_keeps_the_status() {
+        let addr =
+            serve(b"HTTP/1.1 503 Service Unavailable\r\ncontent-length: 100\r\n\r\nshort").await;
+        let unit = RequestWorkUnit::new(&minimal_args(&format!("http://{addr}/"))).unwrap();
+        let result = unit.execute(Instant::now()).await.unwrap();
+        assert_eq!(result.outcome, HttpRequestOutcome::BodyError);
+        assert_eq!(result.status_code, Some(503));
+        assert!(!result.is_full_response());
+        assert_eq!(result.phases.body, None);
+    }
+
+    #[test]
+    fn only_successes_become_assertion_failures() {
+        let outcome = |status: u16, failed_assertions: &[usize]| {
+            response_outcome(
+                http::StatusCode::from_u16(status).unwrap(),
+                failed_assertions,
+            )
+        };
+        assert_eq!(outcome(200, &[]), HttpRequestOutcome::SuccessResponse);
+        assert_eq!(outcome(200, &[0]), HttpRequestOutcome::AssertionFailed);
+        // A 503 failing --expect-status 200 is still a server error.
+        assert_eq!(outcome(503, &[0]), HttpRequestOutcome::ServerErrorResponse);
+        assert_eq!(outcome(404, &[1]), HttpRequestOutcome::ClientErrorResponse);
+    }
+
+    #[tokio::test]
+    async fn refused_connection_is_a_connect_error() {
+        let addr = {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap()
+        };
+        assert_eq!(
+            failed_get(&format!("http://{addr}/")).await,
+            Some(HttpRequestOutcome::ConnectError)
+        );
+    }
+
+    #[tokio::test]
+    async fn connection_closed_before_a_response_is_a_reset() {
+        let addr = serve(b"").await;
+        assert_eq!(
+            failed_get(&format!("http://{addr}/")).await,
+            Some(HttpRequestOutcome::ConnectionReset)
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_handshake_is_a_tls_error() {
+        let addr = serve(b"HTTP/1.1 200 OK\r\n\r\n").await;
+        assert_eq!(
+            failed_get(&format!("https://{addr}/")).await,
+            Some(HttpRequestOutcome::TlsError)
+        );
+    }
+
+    #[tokio::test]
+    async fn unresolvable_host_is_a_dns_error() {
+        let builder = reqwest::Client::builder().dns_resolver(Arc::new(NoSuchHost));
+        assert_eq!(
+            failed_get_with(builder, "http://loadtest.invalid/").await,
+            Some(HttpRequestOutcome::DnsError)
+        );
+    }
 }
@@ -1,123 +1,512 @@
 use std::{
-    sync::Arc,
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
     time::{Duration, SystemTime},
 };
 
 use futures::StreamExt as _;
 use futures::stream::FuturesUnordered;
-use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive as _;
 use tokio::{
     select,
-    sync::Barrier,
-    task::JoinSet,
-    time::{Interval, MissedTickBehavior, interval_at},
+    sync::{
+        Barrier, OwnedSemaphorePermit, Semaphore, TryAcquireError,
+        mpsc::{self, Receiver, Sender, UnboundedSender, error::TrySendError},
+    },
+    task::{JoinHandle, JoinSet},
 };
 use tokio_util::sync::CancellationToken;
 
 use crate::{
+    abort::{self, AbortReason},
+    adaptive::{self, Aimd},
+    arrival::Arrivals,
     cli::Args,
-    error::Result,
-    stats::{Statistics, WorkerStats},
-    work_unit::{ExecutionResult, RequestWorkUnit, WorkUnit},
+    dispatcher::{Dispatcher, TICKET_CAPACITY},
+    error::{AppError, Result},
+    events::{EventLog, EventRecorder},
+    histogram::Histogram,
+    profile::RateProfile,
+    search::SearchResult,
+    stages::StageTarget,
+    stats::{PhaseTimes, Statistics, WorkerStats},
+    timeline::{self, LatencyPoint, Window},
+    work_unit::{ExecutionResult, HttpRequestOutcome, RequestWorkUnit, WorkUnit},
 };
 
 /// Result of a load test run: run timestamp, worker snapshots, and latencies split by success.
 #[derive(Clone)]
 pub(crate) struct RunResult {
     pub run_timestamp: SystemTime,
+    pub status: RunStatus,
+    /// Wall-clock length of the run, from the start of the workers until the last one finished.
+    pub run_duration: Duration,
     pub worker_stats: Vec<WorkerStats>,
-    pub success_latencies: Vec<Duration>,
-    pub non_success_latencies: Vec<Duration>,
+    pub success_latencies: Histogram,
+    pub non_success_latencies: Histogram,
+    /// Response times measured from each request's intended send time (see
+    /// [`ExecutionResult::response_time`]).
+    pub response_times: Histogram,
+    /// Open-loop target rate points `(elapsed, requests_per_second)`; empty for closed-loop runs.
+    /// With --target-latency, the rate the controller chose over time.
+    pub target_rate: Vec<(Duration, f64)>,
+    /// Responses per exact HTTP status code over the whole run, warm-up included.
+    pub status_codes: BTreeMap<u16, u64>,
+    /// Failures per assertion (by index in `Args::assertions`), warm-up included.
+    pub assertion_failures: BTreeMap<usize, u64>,
+    /// Service times until the response headers, of requests that received them.
+    pub time_to_headers: Histogram,
+    /// Service times until the end of the body, of requests that received a full response.
+    pub full_response: Histogram,
+    /// Request bytes (request line, headers and body, counted as HTTP/1.1 messages: an estimate over
+    /// HTTP/2) and response body bytes over the whole run, warm-up included.
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    /// Body sizes of full responses, in bytes.
+    pub response_sizes: Histogram,
+    /// Time spent in each request phase (see [`crate::work_unit::Phases`]).
+    pub phase_times: PhaseTimes,
+    /// Uniform sample of the failed requests, warm-up included, in the order they were sent.
+    pub failure_samples: Vec<ExecutionResult>,
+    /// Latency percentiles per snapshot window (--snapshot-interval), warm-up excluded.
+    pub latency_over_time: Vec<LatencyPoint>,
+    /// Every probe of a throughput search (--search), which this run is the chosen probe of.
+    pub search: Option<SearchResult>,
+    /// Why the run was aborted, if it was (--abort-on-error-rate).
+    pub abort: Option<AbortReason>,
+    /// Request records left out of the event log (--events) because its writer fell behind; for a
+    /// search, over every probe.
+    pub dropped_events: u64,
+    /// Requests not sent because every worker's ticket queue was full.
+    pub dropped_tickets: u64,
+    /// Response times and outcomes the rate controller (--target-latency) or the abort watcher
+    /// (--abort-on-error-rate) missed because it fell behind.
+    pub dropped_feedback: u64,
 }
 
-pub(crate) async fn spawn_workers(args: &Args) -> Result<RunResult> {
+/// How a run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RunStatus {
+    /// Ran for its full duration.
+    Completed,
+    /// Stopped early by Ctrl-C or SIGTERM; the report covers the part that ran.
+    Interrupted,
+    /// Stopped early because the error rate crossed --abort-on-error-rate.
+    Aborted,
+}
+
+/// Bound on waiting for in-flight requests after a run is interrupted or aborted, unless
+/// --drain-timeout sets one.
+const INTERRUPT_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Response times (or outcomes) queued for the rate controller (or the abort watcher) before
+/// further ones are dropped.
+const FEEDBACK_CAPACITY: usize = 4096;
+
+/// What a worker hands back: its periodic snapshots and its final statistics.
+type WorkerOutput = (Vec<WorkerStats>, Statistics);
+
+/// What the dispatcher hands back: the rate the adaptive controller chose over time, if it ran.
+type ChosenRate = Option<Vec<(Duration, f64)>>;
+
+/// Runs the load test described by `args`, sending every request to `events` if given. Cancelling
+/// `interrupt` stops the run, as Ctrl-C does.
+pub(crate) async fn spawn_workers(
+    args: &Args,
+    events: Option<&EventLog>,
+    interrupt: &CancellationToken,
+) -> Result<RunResult> {
     let run_timestamp = SystemTime::now();
-    let concurrency = tokio::runtime::Handle::current().metrics().num_workers();
+    let user_schedule = user_schedule(args);
+    let concurrency = worker_count(args, user_schedule.as_deref());
 
     let start_barrier = Arc::new(Barrier::new(
         concurrency
             .checked_add(1)
             .expect("concurrency overflow for barrier"),
     ));
-    let cancelation_token = CancellationToken::new();
+    // Cancelled on an interrupt, an abort, or once the workers are done.
+    let cancelation_token = interrupt.child_token();
+    // Shared by all workers so that the cap applies to the run as a whole.
+    let in_flight_limit = args.max_in_flight.map(|n| Arc::new(Semaphore::new(n)));
 
     let mut join_set = JoinSet::new();
-    let now = tokio::time::Instant::now();
-
-    let requests_per_second = args.requests_per_second;
-    let period = rps_to_period(requests_per_second);
 
-    let concurrency_u128: u128 = concurrency
-        .try_into()
-        .expect("Concurrency must be less than u128::MAX");
+    let profile = Arc::new(rate_profile(args));
 
-    let iterations = args
-        .duration
-        .as_nanos()
-        .checked_div(period.as_nanos())
-        .expect("period is zero");
-    let iterations_per_worker = iterations
-        .checked_div(concurrency_u128)
-        .expect("concurrency is zero");
+    let mut tickets = Vec::with_capacity(concurrency);
+    let dropped_tickets = Arc::new(AtomicU64::new(0));
+    // Response times flow back to the dispatcher when it adapts the rate to a latency target.
+    let (feedback, feedback_receiver) = feedback_channel(args.adaptive.is_some());
+    // Outcomes flow to the abort watcher when the run stops on too many errors.
+    let (outcomes, outcome_receiver) = feedback_channel(args.abort.is_some());
+    let dropped_feedback = Arc::new(AtomicU64::new(0));
+    let (windows, timeline) = spawn_timeline(concurrency);
 
-    let iterations_per_worker: usize = iterations_per_worker
-        .try_into()
-        .expect("Iterations per worker must be less than usize::MAX");
+    let mut target_rate = if user_schedule.is_some() {
+        Vec::new()
+    } else {
+        profile.points().to_vec()
+    };
 
+    let schedule = user_schedule.as_deref();
     for id in 0..concurrency {
-        let cancelation_token = cancelation_token.clone();
-        let start_barrier = Arc::clone(&start_barrier);
-        let work_unit = RequestWorkUnit::new(args)?;
-        let mut stats = Statistics::new(iterations_per_worker);
-
-        let mut interval = create_interval(id, now, period, concurrency);
-        let load_test_duration = args.duration;
-        join_set.spawn(async move {
-            Worker {
-                id,
-                max_iterations: iterations_per_worker,
-                load_test_duration,
-                start_barrier,
-                cancelation_token,
-                work_unit,
-            }
-            .work(&mut interval, &mut stats)
-            .await
-        });
+        let mut driver = driver(args, schedule, id, concurrency, &mut tickets);
+        let mut stats = Statistics::new(args.histogram_precision);
+
+        let worker = Worker {
+            id,
+            load_test_duration: args.duration,
+            warmup: args.warmup.unwrap_or(Duration::ZERO),
+            drain_timeout: args.drain_timeout,
+            snapshot_interval: args.snapshot_interval,
+            start_barrier: Arc::clone(&start_barrier),
+            cancelation_token: cancelation_token.clone(),
+            in_flight_limit: in_flight_limit.clone(),
+            feedback: feedback.clone(),
+            outcomes: outcomes.clone(),
+            dropped_feedback: Arc::clone(&dropped_feedback),
+            events: events.map(EventLog::recorder),
+            timeline: windows.clone(),
+            work_unit: RequestWorkUnit::new(args)?,
+        };
+        join_set.spawn(async move { worker.work(&mut driver, &mut stats).await });
     }
 
     start_barrier.wait().await;
+    let run_start = tokio::time::Instant::now();
 
-    let mut worker_stats = Vec::new();
-    let mut success_latencies: Vec<Duration> = Vec::new();
-    let mut non_success_latencies: Vec<Duration> = Vec::new();
-    while let Some(result) = join_set.join_next().await {
-        let result = result??;
-        let Some((stats, success, non_success)) = result else {
-            unimplemented!();
-        };
-        worker_stats.extend(stats);
-        success_latencies.extend(success);
-        non_success_latencies.extend(non_success);
-    }
+    drop((feedback, outcomes, windows));
+    let abort_watch =
+        spawn_abort_watch(args, run_start, outcome_receiver, cancelation_token.clone());
+    let dispatcher = spawn_dispatcher(
+        args,
+        Arc::clone(&profile),
+        run_start,
+        tickets,
+        Arc::clone(&dropped_tickets),
+        feedback_receiver,
+        cancelation_token.clone(),
+    );
 
-    worker_stats.sort_by_key(|w| (w.timestamp, w.id));
+    let (status, worker_stats, merged) =
+        wait_for_workers(&mut join_set, interrupt, args.histogram_precision).await?;
+    let run_duration = run_start.elapsed();
+    // The workers are done; an abort watcher still reading their last outcomes must not judge a run
+    // that already ended.
+    cancelation_token.cancel();
+    let abort = match abort_watch {
+        Some(watch) => watch.await?,
+        None => None,
+    };
+    let status = abort.as_ref().map_or(status, |_| RunStatus::Aborted);
+
+    if let Some(dispatcher) = dispatcher
+        && let Some(chosen_rate) = dispatcher.await?
+    {
+        target_rate = chosen_rate;
+    }
 
     Ok(RunResult {
         run_timestamp,
+        status,
+        run_duration,
         worker_stats,
-        success_latencies,
-        non_success_latencies,
+        success_latencies: merged.success_latencies,
+        non_success_latencies: merged.non_success_latencies,
+        response_times: merged.response_times,
+        target_rate,
+        status_codes: merged.status_codes,
+        assertion_failures: merged.assertion_failures,
+        time_to_headers: merged.time_to_headers,
+        full_response: merged.full_response,
+        request_bytes: merged.request_bytes,
+        response_bytes: merged.response_bytes,
+        response_sizes: merged.response_sizes,
+        phase_times: merged.phase_times,
+        failure_samples: in_send_order(merged.failures.into_items()),
+        latency_over_time: timeline.await?,
+        search: None,
+        abort,
+        dropped_events: events.map_or(0, EventLog::dropped),
+        dropped_tickets: dropped_tickets.load(Ordering::Relaxed),
+        dropped_feedback: dropped_feedback.load(Ordering::Relaxed),
     })
 }
 
+/// `results` in the order their requests were sent.
+fn in_send_order(mut results: Vec<ExecutionResult>) -> Vec<ExecutionResult> {
+    results.sort_by_key(|result| result.started);
+    results
+}
+
+/// Waits for every worker; once `interrupt` is cancelled, the workers stop issuing requests and
+/// drain. Returns how the run ended, the workers' snapshots in time order, and their merged
+/// latencies.
+async fn wait_for_workers(
+    join_set: &mut JoinSet<Result<WorkerOutput>>,
+    interrupt: &CancellationToken,
+    precision: u8,
+) -> Result<(RunStatus, Vec<WorkerStats>, Statistics)> {
+    let mut worker_stats = Vec::new();
+    let mut merged = Statistics::new(precision);
+    let status = select! {
+        joined = join_workers(join_set, &mut worker_stats, &mut merged) => {
+            joined?;
+            RunStatus::Completed
+        }
+        () = interrupt.cancelled() => {
+            // The workers see it through their child token; still report what was measured.
+            join_workers(join_set, &mut worker_stats, &mut merged).await?;
+            RunStatus::Interrupted
+        }
+    };
+    worker_stats.sort_by_key(|w| (w.timestamp, w.id));
+    Ok((status, worker_stats, merged))
+}
+
+/// Driver for worker `id`: closed-loop along `user_schedule` if given, otherwise open-loop on a
+/// ticket queue whose sending end is added to `tickets`.
+fn driver(
+    args: &Args,
+    user_schedule: Option<&[(Duration, usize)]>,
+    id: usize,
+    concurrency: usize,
+    tickets: &mut Vec<Sender<tokio::time::Instant>>,
+) -> Driver {
+    if let Some(schedule) = user_schedule {
+        return closed_loop_driver(args, schedule, id, concurrency);
+    }
+    let (sender, receiver) = mpsc::channel(TICKET_CAPACITY);
+    tickets.push(sender);
+    Driver::OpenLoop { tickets: receiver }
+}
+
+/// Closed-loop driver for worker `id`: its share of the virtual users and of the request budget.
+fn closed_loop_driver(
+    args: &Args,
+    user_schedule: &[(Duration, usize)],
+    id: usize,
+    concurrency: usize,
+) -> Driver {
+    Driver::ClosedLoop {
+        users: user_schedule
+            .iter()
+            .map(|&(at, users)| (at, share_for_worker(id, users, concurrency)))
+            .collect(),
+        think_time: args.think_time.unwrap_or(Duration::ZERO),
+        budget: args.requests.map(|requests| {
+            let requests = usize::try_from(requests).expect("request budget fits in usize");
+            share_for_worker(id, requests, concurrency)
+        }),
+    }
+}
+
+/// Channel for response times or outcomes, if `enabled`. When its receiver falls
+/// [`FEEDBACK_CAPACITY`] behind, senders drop what they send.
+fn feedback_channel<T>(enabled: bool) -> (Option<Sender<T>>, Option<Receiver<T>>) {
+    enabled.then(|| mpsc::channel(FEEDBACK_CAPACITY)).unzip()
+}
+
+/// Starts the abort watcher when the run stops on too many errors (--abort-on-error-rate).
+fn spawn_abort_watch(
+    args: &Args,
+    start: tokio::time::Instant,
+    outcomes: Option<Receiver<HttpRequestOutcome>>,
+    cancel: CancellationToken,
+) -> Option<JoinHandle<Option<AbortReason>>> {
+    args.abort
+        .zip(outcomes)
+        .map(|(policy, outcomes)| tokio::spawn(abort::watch(policy, start, outcomes, cancel)))
+}
+
+/// Starts the timeline, which merges the latencies of every worker snapshot into latency
+/// percentiles over time. Workers send their snapshots' latencies to the returned sender.
+fn spawn_timeline(workers: usize) -> (UnboundedSender<Window>, JoinHandle<Vec<LatencyPoint>>) {
+    let (windows, receiver) = mpsc::unbounded_channel();
+    let timeline = tokio::spawn(timeline::collect(workers, receiver));
+    (windows, timeline)
+}
+
+/// Starts the open-loop dispatcher: the rate profile's, or the adaptive controller when `feedback`
+/// carries response times (--target-latency). The adaptive one returns the rate it chose over time.
+/// Tickets no worker has room for are counted in `dropped`. Closed-loop runs have no tickets to
+/// dispatch, and no dispatcher.
+fn spawn_dispatcher(
+    args: &Args,
+    profile: Arc<RateProfile>,
+    start: tokio::time::Instant,
+    tickets: Vec<Sender<tokio::time::Instant>>,
+    dropped: Arc<AtomicU64>,
+    feedback: Option<Receiver<Duration>>,
+    cancel: CancellationToken,
+) -> Option<JoinHandle<ChosenRate>> {
+    if tickets.is_empty() {
+        return None;
+    }
+    let arrivals = Arrivals::new(args.arrival, args.seed);
+    if let (Some(settings), Some(feedback)) = (args.adaptive, feedback) {
+        let aimd = Aimd::new(
+            settings,
+            args.requests_per_second
+                .to_f64()
+                .expect("requests per second fits in f64"),
+        );
+        let run = adaptive::run(
+            aimd,
+            arrivals,
+            args.duration,
+            start,
+            tickets,
+            dropped,
+            feedback,
+            cancel,
+        );
+        return Some(tokio::spawn(async move { Some(run.await) }));
+    }
+    let dispatcher = match args.requests {
+        Some(requests) => Dispatcher::with_budget(profile, arrivals, requests),
+        None => Dispatcher::new(profile, arrivals, args.duration),
+    };
+    Some(tokio::spawn(async move {
+        dispatcher.run(start, tickets, dropped, cancel).await;
+        None
+    }))
+}
+
+/// Number of workers to spawn: --workers, or one per runtime thread.
+fn worker_count(args: &Args, user_schedule: Option<&[(Duration, usize)]>) -> usize {
+    let workers = args
+        .workers
+        .unwrap_or_else(|| tokio::runtime::Handle::current().metrics().num_workers());
+    let Some(schedule) = user_schedule else {
+        return workers;
+    };
+    // Never spawn a worker that has no virtual user to drive.
+    let peak_users = schedule.iter().map(|&(_, users)| users).max().unwrap_or(0);
+    workers.min(peak_users.max(1))
+}
+
+/// Waits for every worker, appending their snapshots to `worker_stats` and merging their latencies
+/// into `merged` as each one finishes.
+async fn join_workers(
+    join_set: &mut JoinSet<Result<WorkerOutput>>,
+    worker_stats: &mut Vec<WorkerStats>,
+    merged: &mut Statistics,
+) -> Result<()> {
+    while let Some(result) = join_set.join_next().await {
+        let (stats, latencies) = result??;
+        worker_stats.extend(stats);
+        merged.success_latencies.merge(&latencies.success_latencies);
+        merged
+            .non_success_latencies
+            .merge(&latencies.non_success_latencies);
+        merged.response_times.merge(&latencies.response_times);
+        merged.time_to_headers.merge(&latencies.time_to_headers);
+        merged.full_response.merge(&latencies.full_response);
+        merged.response_sizes.merge(&latencies.response_sizes);
+        merged.request_bytes = merged
+            .request_bytes
+            .checked_add(latencies.request_bytes)
+            .expect("request_bytes counter overflow");
+        merged.response_bytes = merged
+            .response_bytes
+            .checked_add(latencies.response_bytes)
+            .expect("response_bytes counter overflow");
+        merged.phase_times.merge(&latencies.phase_times);
+        merged.failures.merge(latencies.failures);
+        for (status_code, count) in latencies.status_codes {
+            let total = merged.status_codes.entry(status_code).or_insert(0);
+            *total = total
+                .checked_add(count)
+                .expect("status code counter overflow");
+        }
+        for (index, count) in latencies.assertion_failures {
+            let total = merged.assertion_failures.entry(index).or_insert(0);
+            *total = total
+                .checked_add(count)
+                .expect("assertion counter overflow");
+        }
+    }
+    Ok(())
+}
+
+/// Cancels `interrupt` on Ctrl-C or SIGTERM. Runs for the whole of [`crate::run`], so that a signal
+/// stops whatever is going on: a run, its drain, or a search between probes.
+pub(crate) async fn cancel_on_signal(interrupt: CancellationToken) {
+    shutdown_signal().await;
+    interrupt.cancel();
+}
+
+/// Resolves on Ctrl-C, or on SIGTERM on Unix. Never resolves if the handlers cannot be installed.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        let Ok(mut terminate) = signal(SignalKind::terminate()) else {
+            return std::future::pending().await;
+        };
+        select! {
+            result = tokio::signal::ctrl_c() => {
+                if result.is_err() {
+                    terminate.recv().await;
+                }
+            }
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// How a worker decides when to issue its next request.
+enum Driver {
+    /// Open loop: requests fire on the dispatcher's schedule, whether or not earlier ones have
+    /// completed. Each ticket is the intended send time of one request.
+    OpenLoop {
+        tickets: Receiver<tokio::time::Instant>,
+    },
+    /// Closed loop: each virtual user waits for its response (plus think time) before sending again.
+    /// `users` lists `(elapsed, users)` changes of the user count, starting at zero.
+    ClosedLoop {
+        users: Vec<(Duration, usize)>,
+        think_time: Duration,
+        /// This worker's share of the request budget (-n); the worker stops once it is sent.
+        budget: Option<usize>,
+    },
+}
+
 struct Worker<W> {
     id: usize,
-    max_iterations: usize,
     load_test_duration: Duration,
+    /// Requests scheduled this long after the start are warm-up: counted, latencies left out.
+    warmup: Duration,
+    /// Bound on waiting for in-flight requests after the run (--drain-timeout); `None` waits for
+    /// all, or for [`INTERRUPT_DRAIN_TIMEOUT`] once the run is cancelled.
+    drain_timeout: Option<Duration>,
+    /// Time between snapshots (--snapshot-interval).
+    snapshot_interval: Duration,
     start_barrier: Arc<Barrier>,
     cancelation_token: CancellationToken,
+    /// Permits for requests in flight (--max-in-flight); `None` when unbounded.
+    in_flight_limit: Option<Arc<Semaphore>>,
+    /// Where every response time is reported for adaptive rate control (--target-latency).
+    feedback: Option<Sender<Duration>>,
+    /// Where every outcome is reported for the abort watcher (--abort-on-error-rate).
+    outcomes: Option<Sender<HttpRequestOutcome>>,
+    /// Response times and outcomes left out because the controller or watcher fell behind.
+    dropped_feedback: Arc<AtomicU64>,
+    /// Where every request is recorded for the event log (--events).
+    events: Option<EventRecorder>,
+    /// Where each snapshot's latencies go to build the latency percentiles over time.
+    timeline: UnboundedSender<Window>,
     work_unit: W,
 }
 
@@ -125,22 +514,30 @@ impl<W> Worker<W>
 where
     W: WorkUnit,
 {
-    async fn work(
-        &self,
-        interval: &mut Interval,
-        stats: &mut Statistics,
-    ) -> Result<Option<(Vec<WorkerStats>, Vec<Duration>, Vec<Duration>)>> {
+    async fn work(&self, driver: &mut Driver, stats: &mut Statistics) -> Result<WorkerOutput> {
         self.start_barrier.wait().await;
 
         // Measure the time the worker started so that we can ignore ticks before that.
         let start_time = tokio::time::Instant::now();
 
-        select! {
-            () = self.cancelation_token.cancelled() => {
-                Ok(None)
-            }
-            result = self.do_work(start_time, interval, stats) => {
-                Ok(Some(result?))
+        self.drive(start_time, driver, stats).await
+    }
+
+    async fn drive(
+        &self,
+        start: tokio::time::Instant,
+        driver: &mut Driver,
+        stats: &mut Statistics,
+    ) -> Result<WorkerOutput> {
+        match *driver {
+            Driver::OpenLoop { ref mut tickets } => self.do_work(start, tickets, stats).await,
+            Driver::ClosedLoop {
+                ref users,
+                think_time,
+                budget,
+            } => {
+                self.do_closed_loop_work(start, users, think_time, budget, stats)
+                    .await
             }
         }
     }
@@ -148,74 +545,265 @@ where
     async fn do_work(
         &self,
         start: tokio::time::Instant,
-        interval: &mut Interval,
+        tickets: &mut Receiver<tokio::time::Instant>,
         stats: &mut Statistics,
-    ) -> Result<(Vec<WorkerStats>, Vec<Duration>, Vec<Duration>)> {
-        let mut diagnostics_interval = tokio::time::interval(Duration::from_millis(250));
+    ) -> Result<WorkerOutput> {
+        let mut diagnostics_interval = tokio::time::interval(self.snapshot_interval);
         let mut fu = FuturesUnordered::new();
         let mut current_iteration: usize = 0;
 
         let mut worker_stats = Vec::new();
 
-        while current_iteration < self.max_iterations && start.elapsed() < self.load_test_duration {
-            let elapsed_ns = start.elapsed().as_nanos();
-            let period_ns = interval.period().as_nanos();
-            let expected_count: usize = elapsed_ns
-                .checked_div(period_ns)
-                .and_then(|n| usize::try_from(n).ok())
-                .expect("expected iterations fit in usize");
-
+        loop {
             select! {
-                tick = interval.tick() => {
-                    if tick < start {
-                        continue;
+                () = self.cancelation_token.cancelled() => break,
+                ticket = tickets.recv() => {
+                    // The dispatcher hangs up once the run's requests have all been issued.
+                    let Some(scheduled) = ticket else {
+                        break;
+                    };
+                    match self.try_acquire_in_flight() {
+                        Ok(permit) => {
+                            // Latency is measured from `scheduled`, not from when the ticket arrived.
+                            fu.push(self.execute_once(scheduled, permit));
+                            current_iteration = current_iteration
+                                .checked_add(1)
+                                .expect("current_iteration overflow");
+                        }
+                        Err(TryAcquireError::NoPermits | TryAcquireError::Closed) => {
+                            stats.add_dropped();
+                        }
                     }
+                }
+                _ = diagnostics_interval.tick() => {
+                    self.capture_diagnostics(stats, &fu, start, &mut worker_stats, current_iteration, false);
+                }
+                result = fu.next(), if !fu.is_empty() => {
+                    let Some(result) = result else {
+                        // No more results, we're done.
+                        continue;
+                    };
+                    let r = result?;
+                    self.record(stats, start, &r);
+                }
+            }
+        }
+        self.finish(fu, stats, start, worker_stats, current_iteration)
+            .await
+    }
 
-                    while current_iteration < expected_count && current_iteration < self.max_iterations {
-                        fu.push(self.execute_once());
-                        current_iteration = current_iteration
-                            .checked_add(1)
-                            .expect("current_iteration overflow");
+    async fn do_closed_loop_work(
+        &self,
+        start: tokio::time::Instant,
+        users: &[(Duration, usize)],
+        think_time: Duration,
+        budget: Option<usize>,
+        stats: &mut Statistics,
+    ) -> Result<WorkerOutput> {
+        let mut diagnostics_interval = tokio::time::interval(self.snapshot_interval);
+        let mut fu = FuturesUnordered::new();
+        // Virtual users sleeping through their think time; each completion re-issues one request.
+        let mut thinking = FuturesUnordered::new();
+        let mut current_iteration: usize = 0;
+
+        let mut worker_stats = Vec::new();
+
+        let mut stages = users.iter().copied().peekable();
+        let mut target = stages.next().map_or(0, |(_, users)| users);
+        let next_stage = tokio::time::sleep_until(
+            start
+                .checked_add(stages.peek().map_or(self.load_test_duration, |&(at, _)| at))
+                .expect("stage start overflow"),
+        );
+        tokio::pin!(next_stage);
+
+        // A request budget replaces the time limit: stop once the worker's share has been sent.
+        let may_send = |sent: usize| budget.is_none_or(|budget| sent < budget);
+        while budget.map_or(start.elapsed() < self.load_test_duration, |budget| {
+            current_iteration < budget
+        }) {
+            // Bring the user count up to the stage's target; surplus users retire as they finish.
+            while fu.len().saturating_add(thinking.len()) < target && may_send(current_iteration) {
+                fu.push(self.execute_once(tokio::time::Instant::now(), None));
+                current_iteration = current_iteration
+                    .checked_add(1)
+                    .expect("current_iteration overflow");
+            }
+
+            select! {
+                () = self.cancelation_token.cancelled() => break,
+                () = &mut next_stage, if stages.peek().is_some() => {
+                    target = stages.next().map_or(target, |(_, users)| users);
+                    if let Some(&(at, _)) = stages.peek() {
+                        next_stage
+                            .as_mut()
+                            .reset(start.checked_add(at).expect("stage start overflow"));
                     }
                 }
                 _ = diagnostics_interval.tick() => {
-                    self.capture_diagnostics(stats, &fu, start, &mut worker_stats, current_iteration);
+                    self.capture_diagnostics(stats, &fu, start, &mut worker_stats, current_iteration, false);
                 }
                 result = fu.next(), if !fu.is_empty() => {
                     let Some(result) = result else {
-                        // No more results, we're done.
                         continue;
                     };
                     let r = result?;
-                    stats.add(&r);
+                    self.record(stats, start, &r);
+                    if fu.len().saturating_add(thinking.len()) < target {
+                        thinking.push(tokio::time::sleep(think_time));
+                    }
+                }
+                _ = thinking.next(), if !thinking.is_empty() => {
+                    if fu.len().saturating_add(thinking.len()) < target && may_send(current_iteration) {
+                        fu.push(self.execute_once(tokio::time::Instant::now(), None));
+                        current_iteration = current_iteration
+                            .checked_add(1)
+                            .expect("current_iteration overflow");
+                    }
+                }
+            }
+        }
+        self.finish(fu, stats, start, worker_stats, current_iteration)
+            .await
+    }
+
+    /// Waits for the requests still in flight, then takes the final snapshot. The wait is bounded by
+    /// --drain-timeout; without it, by [`INTERRUPT_DRAIN_TIMEOUT`] from a cancellation, whether it
+    /// came before or during the drain. Requests still running then are cancelled and counted as
+    /// abandoned.
+    async fn finish<F>(
+        &self,
+        mut fu: FuturesUnordered<F>,
+        stats: &mut Statistics,
+        start: tokio::time::Instant,
+        mut worker_stats: Vec<WorkerStats>,
+        current_iteration: usize,
+    ) -> Result<WorkerOutput>
+    where
+        F: Future<Output = Result<ExecutionResult>>,
+    {
+        let drain = async {
+            while let Some(result) = fu.next().await {
+                self.record(stats, start, &result?);
+            }
+            Ok::<(), AppError>(())
+        };
+        tokio::pin!(drain);
+        let deadline = self
+            .drain_timeout
+            .map(|timeout| tokio::time::Instant::now().checked_add(timeout))
+            .map(|deadline| deadline.expect("drain deadline overflow"));
+        let drained = if let Some(deadline) = deadline {
+            tokio::time::timeout_at(deadline, &mut drain).await.ok()
+        } else {
+            // An interruption, before or during the drain, caps what is left of it.
+            select! {
+                drained = &mut drain => Some(drained),
+                () = self.cancelation_token.cancelled() => {
+                    tokio::time::timeout(INTERRUPT_DRAIN_TIMEOUT, &mut drain).await.ok()
                 }
             }
+        };
+        if let Some(drained) = drained {
+            drained?;
         }
-        self.capture_diagnostics(stats, &fu, start, &mut worker_stats, current_iteration);
+        // Dropping the requests that outlived the drain cancels them.
+        stats.add_abandoned(fu.len());
+        fu.clear();
+        self.capture_diagnostics(
+            stats,
+            &fu,
+            start,
+            &mut worker_stats,
+            current_iteration,
+            true,
+        );
+        Ok((worker_stats, std::mem::take(stats)))
+    }
 
-        while let Some(result) = fu.next().await {
-            result?;
+    /// Takes an in-flight permit, failing if --max-in-flight requests are already running. Without a
+    /// limit, always succeeds with no permit.
+    fn try_acquire_in_flight(&self) -> Result<Option<OwnedSemaphorePermit>, TryAcquireError> {
+        match self.in_flight_limit {
+            Some(ref limit) => Arc::clone(limit).try_acquire_owned().map(Some),
+            None => Ok(None),
         }
+    }
 
-        let success_latencies = std::mem::take(&mut stats.success_latencies);
-        let non_success_latencies = std::mem::take(&mut stats.non_success_latencies);
-        Ok((worker_stats, success_latencies, non_success_latencies))
+    /// Executes one request, holding `permit` (if any) until it completes.
+    async fn execute_once(
+        &self,
+        scheduled: tokio::time::Instant,
+        permit: Option<OwnedSemaphorePermit>,
+    ) -> Result<ExecutionResult> {
+        let result = self.work_unit.execute(scheduled.into_std()).await;
+        drop(permit);
+        result
     }
 
-    async fn execute_once(&self) -> Result<ExecutionResult> {
-        self.work_unit.execute().await
+    /// Adds a completed request to `stats`, leaving its latency out if it was scheduled in warm-up.
+    fn record(
+        &self,
+        stats: &mut Statistics,
+        start: tokio::time::Instant,
+        result: &ExecutionResult,
+    ) {
+        // Warm-up responses still steer the rate and count towards an abort. The rate controller and
+        // the abort watcher hang up when the run stops, so a send can fail while draining; that
+        // response is simply not needed any more, and is not counted as dropped.
+        if let Some(ref feedback) = self.feedback {
+            self.count_full(&feedback.try_send(result.response_time()));
+        }
+        if let Some(ref outcomes) = self.outcomes {
+            self.count_full(&outcomes.try_send(result.outcome));
+        }
+        let warmup_end = start
+            .checked_add(self.warmup)
+            .expect("warm-up end overflow")
+            .into_std();
+        // The first ticket is scheduled at the run start, a hair before this worker's `start`; without
+        // a warm-up it must still count.
+        let warmup = !self.warmup.is_zero() && result.scheduled < warmup_end;
+        if let Some(ref events) = self.events {
+            events.record(self.id, warmup, result);
+        }
+        if warmup {
+            stats.add_warmup(result);
+        } else {
+            stats.add(result);
+        }
+    }
+
+    /// Counts a response time or outcome left out because its receiver's queue was full.
+    fn count_full<T>(&self, sent: &Result<(), TrySendError<T>>) {
+        if matches!(*sent, Err(TrySendError::Full(_))) {
+            self.dropped_feedback.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
+    /// Takes a snapshot of `stats` and hands the latencies since the previous one to the timeline.
+    /// `last` marks the worker's final snapshot.
     fn capture_diagnostics<T>(
         &self,
-        stats: &Statistics,
+        stats: &mut Statistics,
         fu: &FuturesUnordered<T>,
         start: tokio::time::Instant,
         worker_stats: &mut Vec<WorkerStats>,
         current_iteration: usize,
+        last: bool,
     ) {
         let elapsed = start.elapsed();
         let in_flight = fu.len();
+        // The timeline outlives the workers; a send only fails if it panicked, which the run reports.
+        self.timeline
+            .send(Window {
+                worker: self.id,
+                index: worker_stats.len(),
+                elapsed,
+                latencies: stats.window.take(),
+                last,
+            })
+            .unwrap_or_default();
         let Statistics {
             ref successful_response,
             ref informational_response,
@@ -224,6 +812,17 @@ where
             ref server_error_response,
             ref other_error_response,
             ref timeouts,
+            ref connect_errors,
+            ref dns_errors,
+            ref tls_errors,
+            ref connection_resets,
+            ref body_errors,
+            ref assertion_failed,
+            ref dropped,
+            ref abandoned,
+            ref status_codes,
+            ref request_bytes,
+            ref response_bytes,
             ..
         } = *stats;
         let id = self.id;
@@ -241,68 +840,267 @@ where
             server_error_response: *server_error_response,
             other_error_response: *other_error_response,
             timeouts: *timeouts,
+            connect_errors: *connect_errors,
+            dns_errors: *dns_errors,
+            tls_errors: *tls_errors,
+            connection_resets: *connection_resets,
+            body_errors: *body_errors,
+            assertion_failed: *assertion_failed,
+            dropped: *dropped,
+            abandoned: *abandoned,
+            status_codes: status_codes.clone(),
+            request_bytes: *request_bytes,
+            response_bytes: *response_bytes,
+            warmup: elapsed < self.warmup,
         });
     }
 }
 
-fn create_interval(
-    id: usize,
-    now: tokio::time::Instant,
-    period: Duration,
-    concurrency: usize,
-) -> Interval {
-    let concurrency: u32 = concurrency
-        .try_into()
-        .expect("Concurrency must be less than u32::MAX");
-    let id: u32 = id.try_into().expect("ID must be less than u32::MAX");
-
-    let worker_period = period
-        .checked_mul(concurrency)
-        .expect("worker period overflow");
-    let start = now
-        .checked_add(period.checked_mul(id).expect("start offset overflow"))
-        .expect("start overflow");
-
-    let mut interval = interval_at(start, worker_period);
-    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
-    interval
+/// Target rate over the run: the `--stages` rates back to back, or `--requests-per-second` with
+/// optional ramps.
+fn rate_profile(args: &Args) -> RateProfile {
+    if let Some(ref stages) = args.stages {
+        return RateProfile::stepped(stages.iter().map(|stage| {
+            let rate = match stage.target {
+                StageTarget::Rate(rps) => rps.to_f64().expect("stage rate fits in f64"),
+                StageTarget::Users(_) => 0.0_f64,
+            };
+            (stage.duration, rate)
+        }));
+    }
+    RateProfile::ramped(
+        args.requests_per_second
+            .to_f64()
+            .expect("requests per second fits in f64"),
+        args.duration,
+        args.ramp_up.unwrap_or_default(),
+        args.ramp_down.unwrap_or_default(),
+    )
 }
 
-/// Converts requests-per-second to period between requests (nanos).
-pub(crate) fn rps_to_period(rps: Decimal) -> Duration {
-    let period = Decimal::ONE
-        .checked_div(rps)
-        .expect("Period must be greater than 0");
-    let billion = Decimal::from(1_000_000_000_u64);
-    let period_ns = period
-        .checked_mul(billion)
-        .expect("Period must be less than 1 billion");
-    let period_ns = period_ns
-        .to_u64()
-        .expect("Period must be less than u64::MAX");
-    Duration::from_nanos(period_ns)
+/// Closed-loop user count as `(elapsed, users)` changes, or `None` for an open-loop run.
+fn user_schedule(args: &Args) -> Option<Vec<(Duration, usize)>> {
+    if let Some(ref stages) = args.stages {
+        let mut at = Duration::ZERO;
+        let mut schedule = Vec::with_capacity(stages.len());
+        for stage in stages {
+            let StageTarget::Users(users) = stage.target else {
+                return None;
+            };
+            schedule.push((at, users));
+            at = at.saturating_add(stage.duration);
+        }
+        return Some(schedule);
+    }
+    args.concurrency.map(|users| vec![(Duration::ZERO, users)])
+}
+
+/// Worker `id`'s share when `total` (virtual users, or a request budget) is spread over
+/// `concurrency` workers. The first `total % concurrency` workers take one extra.
+fn share_for_worker(id: usize, total: usize, concurrency: usize) -> usize {
+    let base = total.checked_div(concurrency).expect("concurrency is zero");
+    let remainder = total.checked_rem(concurrency).expect("concurrency is zero");
+    if id < remainder {
+        base.checked_add(1).expect("users per worker overflow")
+    } else {
+        base
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rust_decimal::Decimal;
 
     #[test]
-    fn rps_to_period_one_per_second() {
-        let period = rps_to_period(Decimal::ONE);
-        assert_eq!(period.as_nanos(), 1_000_000_000);
+    fn share_for_worker_spreads_remainder_over_first_workers() {
+        let users: Vec<usize> = (0..4).map(|id| share_for_worker(id, 10, 4)).collect();
+        assert_eq!(users, vec![3, 3, 2, 2]);
+        assert_eq!(users.iter().sum::<usize>(), 10);
     }
 
     #[test]
-    fn rps_to_period_ten_per_second() {
-        let period = rps_to_period(Decimal::from(10));
-        assert_eq!(period.as_nanos(), 100_000_000);
+    fn share_for_worker_even_split() {
+        assert!((0..3).all(|id| share_for_worker(id, 6, 3) == 2));
     }
 
-    #[test]
-    fn rps_to_period_half_per_second() {
-        let period = rps_to_period(Decimal::new(5, 1)); // 0.5
-        assert_eq!(period.as_nanos(), 2_000_000_000);
+    /// A request that never completes.
+    struct Hang;
+
+    impl WorkUnit for Hang {
+        fn execute(
+            &self,
+            _scheduled: std::time::Instant,
+        ) -> impl Future<Output = Result<ExecutionResult>> + Send + '_ {
+            std::future::pending()
+        }
+    }
+
+    /// A request that succeeds immediately.
+    struct Immediate;
+
+    impl WorkUnit for Immediate {
+        fn execute(
+            &self,
+            scheduled: std::time::Instant,
+        ) -> impl Future<Output = Result<ExecutionResult>> + Send + '_ {
+            std::future::ready(Ok(ExecutionResult {
+                outcome: crate::work_unit::HttpRequestOutcome::SuccessResponse,
+                status_code: Some(200),
+                duration: Duration::ZERO,
+                time_to_headers: Some(Duration::ZERO),
+                scheduled,
+                started: scheduled,
+                phases: crate::work_unit::Phases::default(),
+                request_bytes: 0,
+                response_bytes: 0,
+                failed_assertions: Vec::new(),
+                failure: None,
+            }))
+        }
+    }
+
+    fn test_worker<W>(work_unit: W, drain_timeout: Option<Duration>) -> Worker<W> {
+        Worker {
+            id: 0,
+            load_test_duration: Duration::ZERO,
+            warmup: Duration::ZERO,
+            drain_timeout,
+            snapshot_interval: Duration::from_millis(250),
+            start_barrier: Arc::new(Barrier::new(1)),
+            cancelation_token: CancellationToken::new(),
+            in_flight_limit: None,
+            feedback: None,
+            outcomes: None,
+            dropped_feedback: Arc::new(AtomicU64::new(0)),
+            events: None,
+            timeline: mpsc::unbounded_channel().0,
+            work_unit,
+        }
+    }
+
+    #[tokio::test]
+    async fn closed_loop_budget_sends_exactly_the_worker_share() {
+        let mut driver = Driver::ClosedLoop {
+            users: vec![(Duration::ZERO, 4)],
+            think_time: Duration::ZERO,
+            budget: Some(share_for_worker(0, 25, 2)),
+        };
+
+        let (snapshots, stats) = test_worker(Immediate, None)
+            .work(&mut driver, &mut Statistics::default())
+            .await
+            .unwrap();
+
+        assert_eq!(stats.successful_response, 13);
+        assert_eq!(stats.success_latencies.len(), 13);
+        assert_eq!(snapshots.last().unwrap().request_sent, 13);
+    }
+
+    #[tokio::test]
+    async fn ticket_scheduled_before_worker_start_counts_without_warmup() {
+        let (sender, receiver) = mpsc::channel(TICKET_CAPACITY);
+        sender.try_send(tokio::time::Instant::now()).unwrap();
+        drop(sender);
+        let mut driver = Driver::OpenLoop { tickets: receiver };
+
+        let (_, stats) = test_worker(Immediate, None)
+            .work(&mut driver, &mut Statistics::default())
+            .await
+            .unwrap();
+
+        assert_eq!(stats.success_latencies.len(), 1);
+        assert_eq!(stats.response_times.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn feedback_beyond_the_capacity_is_dropped_and_counted() {
+        let mut worker = test_worker(Immediate, None);
+        let (feedback, _receiver) = mpsc::channel(1);
+        worker.feedback = Some(feedback);
+        let (sender, receiver) = mpsc::channel(TICKET_CAPACITY);
+        for _ in 0..3 {
+            sender.try_send(tokio::time::Instant::now()).unwrap();
+        }
+        drop(sender);
+        let mut driver = Driver::OpenLoop { tickets: receiver };
+
+        let (_, stats) = worker
+            .work(&mut driver, &mut Statistics::default())
+            .await
+            .unwrap();
+
+        assert_eq!(stats.successful_response, 3);
+        assert_eq!(worker.dropped_feedback.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn drain_timeout_abandons_requests_still_in_flight() {
+        let worker = test_worker(Hang, Some(Duration::from_millis(50)));
+        let (sender, receiver) = mpsc::channel(TICKET_CAPACITY);
+        for _ in 0..3 {
+            sender.try_send(tokio::time::Instant::now()).unwrap();
+        }
+        drop(sender);
+        let mut driver = Driver::OpenLoop { tickets: receiver };
+
+        let (snapshots, stats) = worker
+            .work(&mut driver, &mut Statistics::default())
+            .await
+            .unwrap();
+
+        assert_eq!(stats.abandoned, 3);
+        let last = snapshots.last().unwrap();
+        assert_eq!(
+            (last.request_sent, last.in_flight, last.abandoned),
+            (3, 0, 3)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancellation_during_drain_bounds_the_rest_of_it() {
+        let worker = test_worker(Hang, None);
+        let cancel = worker.cancelation_token.clone();
+        let (sender, receiver) = mpsc::channel(TICKET_CAPACITY);
+        sender.try_send(tokio::time::Instant::now()).unwrap();
+        drop(sender);
+        let mut driver = Driver::OpenLoop { tickets: receiver };
+
+        // The run ends at once and the drain waits on the hung request until the interruption.
+        let began = tokio::time::Instant::now();
+        let interrupt = async {
+            tokio::time::sleep(Duration::from_mins(1)).await;
+            cancel.cancel();
+        };
+        let mut stats = Statistics::default();
+        let ((), output) = tokio::join!(interrupt, worker.work(&mut driver, &mut stats));
+        let (_, stats) = output.unwrap();
+
+        assert_eq!(stats.abandoned, 1);
+        assert_eq!(
+            began.elapsed(),
+            Duration::from_mins(1).saturating_add(INTERRUPT_DRAIN_TIMEOUT)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancellation_keeps_a_longer_drain_timeout() {
+        let worker = test_worker(Hang, Some(Duration::from_mins(1)));
+        let cancel = worker.cancelation_token.clone();
+        let (sender, receiver) = mpsc::channel(TICKET_CAPACITY);
+        sender.try_send(tokio::time::Instant::now()).unwrap();
+        drop(sender);
+        let mut driver = Driver::OpenLoop { tickets: receiver };
+
+        let began = tokio::time::Instant::now();
+        let interrupt = async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            cancel.cancel();
+        };
+        let mut stats = Statistics::default();
+        let ((), output) = tokio::join!(interrupt, worker.work(&mut driver, &mut stats));
+        let (_, stats) = output.unwrap();
+
+        assert_eq!(stats.abandoned, 1);
+        assert_eq!(began.elapsed(), Duration::from_mins(1));
     }
 }